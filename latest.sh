@@ -134,7 +134,7 @@ install() {
     else
         printf '%s\n' 'info: installing libncurses if needed' 1>&2
     fi
-    ignore sudo apt-get install $1 libncurses5-dev
+    ignore sudo apt-get install $1 libncurses5-dev libncursesw5-dev
 
     if $_ansi_escapes_are_valid; then
         printf "\33[1minfo:\33[0m installing viewerator in ${VIEWERATOR_INSTALL_DIR}\n" 1>&2
@@ -0,0 +1,145 @@
+//
+// Plots series on a Braille canvas, where each character cell holds a 2x4 grid
+// of dots, so a chart gets twice the columns and four times the rows of the
+// characters it occupies.
+//
+
+// bit for each dot, indexed by [row][column] within the cell
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+}
+
+pub fn summarize(values: &[f64]) -> Option<Summary> {
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    Some(Summary { min, max, avg })
+}
+
+//
+// Merges several bucketed series into one, either summing (for things like
+// power and hashrate) or averaging (temperatures, voltages) the values present
+// in each bucket.
+//
+pub fn combine(series: &[Vec<Option<f64>>], sum: bool) -> Vec<Option<f64>> {
+    let width = series.iter().map(|s| s.len()).max().unwrap_or(0);
+    (0..width)
+        .map(|i| {
+            let present: Vec<f64> = series.iter().filter_map(|s| s.get(i).cloned().flatten()).collect();
+            if present.is_empty() {
+                None
+            } else if sum {
+                Some(present.iter().sum())
+            } else {
+                Some(present.iter().sum::<f64>() / present.len() as f64)
+            }
+        })
+        .collect()
+}
+
+//
+// A cell of the plot, with the index of the last series drawn through it so
+// overlays can be colored per series.
+//
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub series: Option<usize>,
+}
+
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    bits: Vec<u32>,
+    owner: Vec<Option<usize>>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Canvas {
+        Canvas {
+            width,
+            height,
+            bits: vec![0; width * height],
+            owner: vec![None; width * height],
+        }
+    }
+
+    pub fn dot_width(&self) -> usize {
+        self.width * 2
+    }
+
+    pub fn dot_height(&self) -> usize {
+        self.height * 4
+    }
+
+    fn set(&mut self, x: usize, y: usize, series: usize) {
+        if x >= self.dot_width() || y >= self.dot_height() {
+            return;
+        }
+        let cell = (y / 4) * self.width + x / 2;
+        self.bits[cell] |= DOTS[y % 4][x % 2];
+        self.owner[cell] = Some(series);
+    }
+
+    //
+    // Draws one series, one value per dot column, scaled so min is the bottom
+    // row and max the top.  Consecutive points are joined with a vertical run so
+    // steep changes stay visible.  Missing values leave a gap.
+    //
+    pub fn plot(&mut self, series: usize, values: &[Option<f64>], min: f64, max: f64) {
+        let rows = self.dot_height();
+        if rows == 0 {
+            return;
+        }
+        let range = if max > min { max - min } else { 1.0 };
+        let mut last: Option<usize> = None;
+        for (x, v) in values.iter().enumerate().take(self.dot_width()) {
+            let v = match v {
+                Some(v) => *v,
+                None => {
+                    last = None;
+                    continue;
+                }
+            };
+            let scaled = ((v - min) / range).clamp(0.0, 1.0);
+            let y = ((1.0 - scaled) * (rows - 1) as f64).round() as usize;
+            let (from, to) = match last {
+                Some(prev) if prev < y => (prev + 1, y),
+                Some(prev) if prev > y => (y, prev - 1),
+                _ => (y, y),
+            };
+            for dot in from..=to {
+                self.set(x, dot, series);
+            }
+            last = Some(y);
+        }
+    }
+
+    pub fn rows(&self) -> Vec<Vec<Cell>> {
+        (0..self.height)
+            .map(|row| {
+                (0..self.width)
+                    .map(|col| {
+                        let cell = row * self.width + col;
+                        let ch = match self.bits[cell] {
+                            0 => ' ',
+                            bits => std::char::from_u32(0x2800 + bits).unwrap_or(' '),
+                        };
+                        Cell {
+                            ch,
+                            series: self.owner[cell],
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,202 @@
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::history;
+use crate::keymap;
+use crate::layout;
+use crate::profit;
+use crate::theme;
+
+pub const CONFIG_FILE: &str = "viewerator.json";
+
+//
+// Persistent settings kept as JSON in the config directory.  Every field has a
+// default so older or hand edited files keep loading as new settings get added.
+//
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    pub hosts: Vec<String>,
+    pub history_retention_secs: u64,
+    pub history_capacity: usize,
+    pub sparkline_minutes: u64,
+    pub bad_nonce_percent: Threshold,
+    pub valid_percent: Threshold,
+    pub reject_percent: Threshold,
+    // revenue and cost estimates are only shown once this is filled in
+    pub profit: Option<profit::Profit>,
+    // hashrate unit per algorithm, like "eaglesong": "GH", otherwise picked to fit
+    pub hashrate_units: BTreeMap<String, String>,
+    pub regulator: Regulator,
+    // key preset and any keys remapped from it
+    pub keys: keymap::Bindings,
+    pub theme: theme::Settings,
+    // off leaves the mouse to the terminal, for selecting text
+    pub mouse: bool,
+    // panels hidden or zoomed, saved as they are changed
+    pub layout: layout::Preferences,
+    #[serde(skip)]
+    dir: Option<String>,
+}
+
+//
+// How far the vccint regulator may stray before it is flagged.  Droop and vout
+// imbalance are in percent, the phase temperature imbalance in degrees.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Regulator {
+    pub droop_percent: Threshold,
+    pub vout_imbalance_percent: Threshold,
+    pub temperature_imbalance: Threshold,
+}
+
+impl Default for Regulator {
+    fn default() -> Self {
+        Regulator {
+            droop_percent: Threshold::new(2.0, 4.0),
+            vout_imbalance_percent: Threshold::new(1.0, 2.0),
+            temperature_imbalance: Threshold::new(5.0, 10.0),
+        }
+    }
+}
+
+//
+// Warn and critical levels for a derived figure.  When critical is below warn
+// the figure is one where lower is worse, like the valid percentage.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Threshold {
+    pub warn: f32,
+    pub critical: f32,
+}
+
+impl Threshold {
+    pub fn new(warn: f32, critical: f32) -> Threshold {
+        Threshold { warn, critical }
+    }
+
+    //
+    // The health level to color the value with, "" when it's fine.
+    //
+    pub fn health(&self, value: f32) -> &'static str {
+        let past = |limit: f32| {
+            if self.critical >= self.warn {
+                value >= limit
+            } else {
+                value <= limit
+            }
+        };
+        if past(self.critical) {
+            "critical"
+        } else if past(self.warn) {
+            "hold"
+        } else {
+            ""
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            hosts: vec![],
+            history_retention_secs: history::DEFAULT_RETENTION_SECS,
+            history_capacity: history::DEFAULT_CAPACITY,
+            sparkline_minutes: 10,
+            bad_nonce_percent: Threshold::new(2.0, 5.0),
+            valid_percent: Threshold::new(98.0, 95.0),
+            reject_percent: Threshold::new(1.0, 3.0),
+            profit: None,
+            hashrate_units: BTreeMap::new(),
+            regulator: Regulator::default(),
+            keys: keymap::Bindings::default(),
+            theme: theme::Settings::default(),
+            mouse: true,
+            layout: layout::Preferences::default(),
+            dir: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn path(config_dir: &str) -> PathBuf {
+        Path::new(config_dir).join(CONFIG_FILE)
+    }
+
+    //
+    // Reads the config from the config directory, falling back to defaults when
+    // there is no file yet or it can't be parsed.  One that can't be parsed is
+    // never saved back over, so a typo doesn't cost the rest of the file.
+    //
+    pub fn load(config_dir: &str) -> Config {
+        let path = Config::path(config_dir);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(_) => {
+                debug!("No config at {}, using defaults", path.display());
+                return Config {
+                    dir: Some(config_dir.to_string()),
+                    ..Config::default()
+                };
+            }
+        };
+        match serde_json::from_str::<Config>(&input) {
+            Ok(mut config) => {
+                config.dir = Some(config_dir.to_string());
+                config
+            }
+            Err(err) => {
+                warn!("Error parsing {}: {}, using defaults", path.display(), err);
+                Config::default()
+            }
+        }
+    }
+
+    pub fn save(&self, config_dir: &str) -> io::Result<()> {
+        let path = Config::path(config_dir);
+        let output = serde_json::to_string_pretty(self)?;
+        fs::write(&path, output)?;
+        info!("Saved config to {}", path.display());
+        Ok(())
+    }
+
+    //
+    // False when the file in the config directory couldn't be parsed, so
+    // saving would replace it.
+    //
+    pub fn writable(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    //
+    // Saves back to the directory the config was loaded from, refusing when it
+    // wasn't loaded from one or the file there couldn't be parsed.
+    //
+    pub fn save_back(&self) -> io::Result<()> {
+        match &self.dir {
+            Some(dir) => self.save(dir),
+            None => Err(io::Error::other(
+                "the config file couldn't be parsed, fix it to save changes",
+            )),
+        }
+    }
+
+    //
+    // Adds any hosts not already in the fleet, returning how many were new.
+    //
+    pub fn add_hosts(&mut self, hosts: &[String]) -> usize {
+        let mut added = 0;
+        for host in hosts {
+            if !self.hosts.contains(host) {
+                self.hosts.push(host.clone());
+                added += 1;
+            }
+        }
+        added
+    }
+}
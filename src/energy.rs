@@ -0,0 +1,180 @@
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::snapshot;
+use crate::webdata;
+
+// where the totals were kept before they were split per host
+pub const ENERGY_FILE: &str = "energy.json";
+const SAVE_INTERVAL_SECS: u64 = 60;
+const JOULES_PER_KWH: f64 = 3_600_000.0;
+
+//
+// kWh used by each device, integrated from input_power between readings and
+// kept in the config directory, a file per host, so the totals carry across
+// restarts.  Days are local dates, which is what a colocation bill goes by.
+//
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Energy {
+    pub since: String,
+    pub devices: BTreeMap<String, DeviceEnergy>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+    #[serde(skip)]
+    saved: Option<SystemTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct DeviceEnergy {
+    pub name: String,
+    pub total_kwh: f64,
+    pub daily_kwh: BTreeMap<String, f64>,
+    #[serde(skip)]
+    last: Option<(SystemTime, f32)>,
+}
+
+pub fn path(config_dir: &str, host: &str) -> PathBuf {
+    snapshot::host_file(config_dir, "energy", host)
+}
+
+pub fn local_date(time: SystemTime) -> String {
+    let time: chrono::DateTime<chrono::Local> = time.into();
+    time.format("%Y-%m-%d").to_string()
+}
+
+impl Energy {
+    //
+    // Picks up the totals saved for host in the config directory, starting
+    // afresh when there are none, and saves back there from then on.  A file
+    // that doesn't parse is never written over, so this session's totals
+    // aren't kept.  Totals from before they were kept per host go to the first
+    // host watched.
+    //
+    pub fn load(config_dir: &str, host: &str) -> Energy {
+        let path = path(config_dir, host);
+        let legacy = Path::new(config_dir).join(ENERGY_FILE);
+        if !path.exists() && legacy.exists() {
+            match fs::rename(&legacy, &path) {
+                Ok(()) => info!("Moved {} to {}", legacy.display(), path.display()),
+                Err(err) => warn!("Unable to move {} to {}: {}", legacy.display(), path.display(), err),
+            }
+        }
+        match fs::read_to_string(&path) {
+            Ok(input) => match serde_json::from_str::<Energy>(&input) {
+                Ok(mut energy) => {
+                    energy.file = Some(path);
+                    energy
+                }
+                Err(err) => {
+                    warn!("Error parsing {}: {}, not saving energy totals", path.display(), err);
+                    Energy::default()
+                }
+            },
+            Err(_) => Energy {
+                file: Some(path),
+                ..Energy::default()
+            },
+        }
+    }
+
+    pub fn writable(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn record(&mut self, time: SystemTime, workers: &[webdata::Worker]) {
+        self.record_on(&local_date(time), time, workers);
+    }
+
+    //
+    // Adds the energy each device used since its last reading, averaging the
+    // power at both ends, and books it to the given date.
+    //
+    pub fn record_on(&mut self, date: &str, time: SystemTime, workers: &[webdata::Worker]) {
+        if self.since.is_empty() {
+            self.since = date.to_string();
+        }
+        for w in workers {
+            let device = self.devices.entry(w.hw_uid.clone()).or_default();
+            device.name = w.name.clone();
+            if let Some((last_time, last_power)) = device.last {
+                let secs = time.duration_since(last_time).map_or(0.0, |d| d.as_secs_f64());
+                if secs > 0.0 && secs <= webdata::MAX_GAP.as_secs_f64() {
+                    let kwh = f64::from(last_power + w.input_power) / 2.0 * secs / JOULES_PER_KWH;
+                    device.total_kwh += kwh;
+                    *device.daily_kwh.entry(date.to_string()).or_default() += kwh;
+                }
+            }
+            device.last = Some((time, w.input_power));
+        }
+    }
+
+    pub fn today(&self, hw_uid: &str, date: &str) -> f64 {
+        self.devices
+            .get(hw_uid)
+            .and_then(|d| d.daily_kwh.get(date))
+            .cloned()
+            .unwrap_or(0.0)
+    }
+
+    pub fn total(&self, hw_uid: &str) -> f64 {
+        self.devices.get(hw_uid).map_or(0.0, |d| d.total_kwh)
+    }
+
+    pub fn rig_today(&self, date: &str) -> f64 {
+        self.devices.keys().map(|hw_uid| self.today(hw_uid, date)).sum()
+    }
+
+    pub fn rig_total(&self) -> f64 {
+        self.devices.values().map(|d| d.total_kwh).sum()
+    }
+
+    //
+    // Daily totals as CSV, one line per device and day followed by a rig line
+    // for each day.
+    //
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("date,device,name,kwh\n");
+        let mut rig: BTreeMap<&str, f64> = BTreeMap::new();
+        for (hw_uid, device) in &self.devices {
+            for (date, kwh) in &device.daily_kwh {
+                out.push_str(&format!("{},{},{},{:.3}\n", date, hw_uid, device.name, kwh));
+                *rig.entry(date).or_default() += kwh;
+            }
+        }
+        for (date, kwh) in rig {
+            out.push_str(&format!("{},rig,,{:.3}\n", date, kwh));
+        }
+        out
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        if let Some(file) = &self.file {
+            fs::write(file, serde_json::to_string_pretty(self)?)?;
+            self.saved = Some(SystemTime::now());
+        }
+        Ok(())
+    }
+
+    //
+    // Saves at most once a minute, so a crash loses little without rewriting
+    // the file on every refresh.
+    //
+    pub fn save_if_due(&mut self, now: SystemTime) {
+        let due = match self.saved {
+            Some(saved) => now.duration_since(saved).unwrap_or_default() >= Duration::from_secs(SAVE_INTERVAL_SECS),
+            None => true,
+        };
+        if due {
+            if let Err(err) = self.save() {
+                warn!("Unable to save energy totals: {}", err);
+            }
+        }
+    }
+}
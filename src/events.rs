@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use crate::health;
+use crate::webdata;
+
+pub const DEFAULT_CAPACITY: usize = 500;
+
+//
+// Something that changed between two consecutive status responses.  The level
+// is the health the change went to, so the pane can color it, or "" for
+// changes that are neither good nor bad.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub time: SystemTime,
+    pub device: String,
+    pub message: String,
+    pub level: String,
+}
+
+impl Event {
+    pub fn timestamp(&self) -> String {
+        let time: chrono::DateTime<chrono::Local> = self.time.into();
+        time.format("%H:%M:%S").to_string()
+    }
+}
+
+//
+// What we remember of each device to compare the next response against.
+//
+struct DeviceState {
+    hw_uid: String,
+    name: String,
+    worse_health: String,
+    fields: Vec<(health::Field, String)>,
+}
+
+impl DeviceState {
+    fn new(w: &webdata::Worker) -> DeviceState {
+        DeviceState {
+            hw_uid: w.hw_uid.clone(),
+            name: w.name.clone(),
+            worse_health: w.worse_health.clone(),
+            fields: health::fields(w)
+                .into_iter()
+                .map(|(field, level)| (field, level.to_string()))
+                .collect(),
+        }
+    }
+}
+
+//
+// Bounded log of the changes seen between responses, oldest first.
+//
+pub struct Events {
+    capacity: usize,
+    events: VecDeque<Event>,
+    devices: Option<Vec<DeviceState>>,
+    pool: Option<String>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Events {
+    pub fn new(capacity: usize) -> Events {
+        Events {
+            capacity: capacity.max(1),
+            events: VecDeque::new(),
+            devices: None,
+            pool: None,
+        }
+    }
+
+    pub fn events(&self) -> &VecDeque<Event> {
+        &self.events
+    }
+
+    pub fn push(&mut self, time: SystemTime, device: &str, message: String, level: &str) {
+        self.events.push_back(Event {
+            time,
+            device: device.to_string(),
+            message,
+            level: level.to_string(),
+        });
+        while self.events.len() > self.capacity {
+            self.events.pop_front();
+        }
+    }
+
+    //
+    // Compares the workers with the previous response and records what changed.
+    // The first response only sets the baseline.
+    //
+    pub fn record_workers(&mut self, time: SystemTime, workers: &[webdata::Worker]) {
+        let current: Vec<DeviceState> = workers.iter().map(DeviceState::new).collect();
+        if let Some(previous) = self.devices.take() {
+            for old in &previous {
+                if !current.iter().any(|d| d.hw_uid == old.hw_uid) {
+                    self.push(time, &old.name, "disappeared".to_string(), "critical");
+                }
+            }
+            for new in &current {
+                match previous.iter().find(|d| d.hw_uid == new.hw_uid) {
+                    Some(old) => self.diff(time, old, new),
+                    None => self.push(time, &new.name, "appeared".to_string(), ""),
+                }
+            }
+        }
+        self.devices = Some(current);
+
+        // the worksource is the same for every device
+        if let Some(w) = workers.first() {
+            let pool = &w.worksource.stats.name;
+            match &self.pool {
+                Some(old) if old != pool => {
+                    let message = format!("pool switched from {} to {}", old, pool);
+                    self.push(time, "rig", message, "hold");
+                }
+                _ => {}
+            }
+            self.pool = Some(pool.clone());
+        }
+    }
+
+    fn diff(&mut self, time: SystemTime, old: &DeviceState, new: &DeviceState) {
+        if old.worse_health != new.worse_health {
+            let message = format!("worst health {} -> {}", old.worse_health, new.worse_health);
+            self.push(time, &new.name, message, &new.worse_health);
+        }
+        for (field, level) in &new.fields {
+            match old.fields.iter().find(|(f, _)| f == field) {
+                Some((_, old_level)) if old_level != level => {
+                    let message = format!("{} {} -> {}", field.label(), old_level, level);
+                    self.push(time, &new.name, message, level);
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use std::cmp::Ordering;
+
+use crate::health;
+use crate::metrics;
+use crate::webdata;
+
+//
+// The columns of the fleet table, left to right.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Index,
+    Name,
+    HwUid,
+    Board,
+    Health,
+    Hottest,
+    Power,
+    Multiplier,
+    Accepted,
+    BadNonce,
+}
+
+pub const COLUMNS: [Column; 10] = [
+    Column::Index,
+    Column::Name,
+    Column::HwUid,
+    Column::Board,
+    Column::Health,
+    Column::Hottest,
+    Column::Power,
+    Column::Multiplier,
+    Column::Accepted,
+    Column::BadNonce,
+];
+
+impl Column {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Index => "#",
+            Column::Name => "Name",
+            Column::HwUid => "hwUID",
+            Column::Board => "Board",
+            Column::Health => "Health",
+            Column::Hottest => "Hottest",
+            Column::Power => "Power",
+            Column::Multiplier => "Mult",
+            Column::Accepted => "Accepted",
+            Column::BadNonce => "BadNonce",
+        }
+    }
+
+    pub fn width(&self) -> i32 {
+        match self {
+            Column::Index => 3,
+            Column::Name => 16,
+            Column::HwUid => 18,
+            Column::Board => 8,
+            Column::Health => 14,
+            Column::Hottest => 9,
+            Column::Power => 9,
+            Column::Multiplier => 7,
+            Column::Accepted => 10,
+            Column::BadNonce => 9,
+        }
+    }
+
+    pub fn next(&self) -> Column {
+        let i = COLUMNS.iter().position(|c| c == self).unwrap_or(0);
+        COLUMNS[(i + 1) % COLUMNS.len()]
+    }
+
+    pub fn previous(&self) -> Column {
+        let i = COLUMNS.iter().position(|c| c == self).unwrap_or(0);
+        COLUMNS[(i + COLUMNS.len() - 1) % COLUMNS.len()]
+    }
+}
+
+//
+// One device as the table shows it.  index is the device's position in the
+// response, which is what the number keys select.  The accepted rate is for
+// the last minute, in minerator's stat units.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Row {
+    pub index: usize,
+    pub name: String,
+    pub hw_uid: String,
+    pub board: &'static str,
+    pub health: String,
+    pub hottest: Option<f32>,
+    pub power: f32,
+    pub multiplier: Option<f32>,
+    pub accepted: f32,
+    pub bad_nonce: Option<f32>,
+}
+
+impl Row {
+    pub fn new(index: usize, w: &webdata::Worker) -> Row {
+        let clock = w.cores.cores.first().map(|core| &core.clock);
+        Row {
+            index,
+            name: w.name.clone(),
+            hw_uid: w.hw_uid.clone(),
+            board: board_name(w.hw_type),
+            health: w.worse_health.clone(),
+            hottest: w
+                .sysmons
+                .sysmon
+                .iter()
+                .map(|s| s.temperature)
+                .fold(None, |max: Option<f32>, t| Some(max.map_or(t, |m| m.max(t)))),
+            power: w.input_power,
+            multiplier: clock.map(|c| c.multiplier),
+            accepted: metrics::accepted_rates(std::slice::from_ref(w)).1,
+            bad_nonce: clock.and_then(metrics::bad_nonce_percent),
+        }
+    }
+
+    fn compare(&self, other: &Row, column: Column) -> Ordering {
+        match column {
+            Column::Index => self.index.cmp(&other.index),
+            Column::Name => self.name.cmp(&other.name),
+            Column::HwUid => self.hw_uid.cmp(&other.hw_uid),
+            Column::Board => self.board.cmp(other.board),
+            Column::Health => health_rank(&self.health).cmp(&health_rank(&other.health)),
+            Column::Hottest => compare_option(self.hottest, other.hottest),
+            Column::Power => compare_option(Some(self.power), Some(other.power)),
+            Column::Multiplier => compare_option(self.multiplier, other.multiplier),
+            Column::Accepted => compare_option(Some(self.accepted), Some(other.accepted)),
+            Column::BadNonce => compare_option(self.bad_nonce, other.bad_nonce),
+        }
+    }
+}
+
+pub fn board_name(hw_type: u16) -> &'static str {
+    match hw_type {
+        webdata::HWTYPE_BCU => "BCU1525",
+        webdata::HWTYPE_CVP => "CVP13",
+        _ => "unknown",
+    }
+}
+
+//
+// Worst health first, anything not in LEVELS after the rest.
+//
+fn health_rank(level: &str) -> usize {
+    health::LEVELS
+        .iter()
+        .position(|l| *l == level)
+        .unwrap_or(health::LEVELS.len())
+}
+
+//
+// Missing and NaN readings sort below every real one.
+//
+fn compare_option(a: Option<f32>, b: Option<f32>) -> Ordering {
+    let a = a.filter(|a| !a.is_nan());
+    let b = b.filter(|b| !b.is_nan());
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+//
+// A row per device sorted on column, ties broken by device index.  Descending
+// puts the hottest, biggest or worst health first.
+//
+pub fn rows(workers: &[webdata::Worker], column: Column, descending: bool) -> Vec<Row> {
+    let mut rows: Vec<Row> = workers.iter().enumerate().map(|(i, w)| Row::new(i, w)).collect();
+    rows.sort_by(|a, b| {
+        let mut order = a.compare(b, column);
+        // health ranks worst lowest, so it flips to match the other columns
+        if column == Column::Health {
+            order = order.reverse();
+        }
+        if descending {
+            order = order.reverse();
+        }
+        order.then(a.index.cmp(&b.index))
+    });
+    rows
+}
+
+//
+// The device a jump prompt answer means: a number is the device's position
+// counting from 1, anything else the first device whose name or hwUID starts
+// with it, ignoring case.
+//
+pub fn find(workers: &[webdata::Worker], query: &str) -> Option<usize> {
+    let query = query.trim();
+    if query.is_empty() {
+        return None;
+    }
+    if let Ok(n) = query.parse::<usize>() {
+        if n >= 1 && n <= workers.len() {
+            return Some(n - 1);
+        }
+    }
+    let query = query.to_lowercase();
+    workers
+        .iter()
+        .position(|w| w.name.to_lowercase().starts_with(&query))
+        .or_else(|| workers.iter().position(|w| w.hw_uid.to_lowercase().starts_with(&query)))
+}
+
+//
+// Which devices the device bar has room for when it fits slots of them,
+// keeping current in view with room either side of it where there is some.
+//
+pub fn visible(count: usize, current: usize, slots: usize) -> std::ops::Range<usize> {
+    let slots = slots.max(1);
+    if count <= slots {
+        return 0..count;
+    }
+    let start = current.saturating_sub(slots / 2).min(count - slots);
+    start..start + slots
+}
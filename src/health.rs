@@ -0,0 +1,150 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, SystemTime};
+
+use crate::webdata;
+
+// minerator's health levels, worst first
+pub const LEVELS: [&str; 5] = ["critical", "slowDecrease", "hold", "slowIncrease", "rampUp"];
+
+//
+// What minerator is doing about the clock at each level, for the help legend.
+//
+pub fn meaning(level: &str) -> &'static str {
+    match level {
+        "critical" => "past its limit, clock cut hard",
+        "slowDecrease" => "near its limit, clock easing down",
+        "hold" => "at its limit, clock held",
+        "slowIncrease" => "some headroom, clock easing up",
+        "rampUp" => "plenty of headroom, clock ramping up",
+        _ => "",
+    }
+}
+
+//
+// The sensor health fields minerator reports for a device.  The vrPower health
+// covers both phases so it is only tracked once.
+//
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Field {
+    InputPower,
+    AuxCurrent,
+    PexCurrent,
+    Aux12V,
+    Pex12V,
+    VccintCurrent,
+    VrCtrl,
+    VrPower,
+    Clock,
+    Sysmon(usize),
+}
+
+impl Field {
+    pub fn label(&self) -> String {
+        match self {
+            Field::InputPower => "Input Power".to_string(),
+            Field::AuxCurrent => "AUX Current".to_string(),
+            Field::PexCurrent => "PEX Current".to_string(),
+            Field::Aux12V => "AUX 12V".to_string(),
+            Field::Pex12V => "PEX 12V".to_string(),
+            Field::VccintCurrent => "VCCINT Current".to_string(),
+            Field::VrCtrl => "VRCTRL".to_string(),
+            Field::VrPower => "VR Power".to_string(),
+            Field::Clock => "Clock".to_string(),
+            Field::Sysmon(n) => format!("Sysmon {}", n),
+        }
+    }
+}
+
+//
+// How long one field has spent at each level and how often it changed level.
+// The time between two readings is credited to the level of the first, unless
+// they are too far apart to say what happened in between.
+//
+#[derive(Clone, Debug, Default)]
+pub struct Residency {
+    pub time: BTreeMap<String, Duration>,
+    pub transitions: u32,
+    current: Option<(String, SystemTime)>,
+}
+
+impl Residency {
+    pub fn record(&mut self, time: SystemTime, level: &str) {
+        if let Some((current, since)) = &self.current {
+            let spent = time.duration_since(*since).unwrap_or_default();
+            if spent <= webdata::MAX_GAP {
+                *self.time.entry(current.clone()).or_default() += spent;
+            }
+            if current != level {
+                self.transitions += 1;
+            }
+        }
+        self.current = Some((level.to_string(), time));
+    }
+
+    pub fn level(&self) -> Option<&str> {
+        self.current.as_ref().map(|(level, _)| level.as_str())
+    }
+
+    pub fn total(&self) -> Duration {
+        self.time.values().sum()
+    }
+
+    //
+    // Share of the tracked time spent at level, None until any time has passed.
+    //
+    pub fn percent(&self, level: &str) -> Option<f64> {
+        let total = self.total().as_secs_f64();
+        if total > 0.0 {
+            let spent = self.time.get(level).cloned().unwrap_or_default();
+            Some(spent.as_secs_f64() / total * 100.0)
+        } else {
+            None
+        }
+    }
+}
+
+pub fn fields(w: &webdata::Worker) -> Vec<(Field, &str)> {
+    let mut fields = vec![
+        (Field::InputPower, w.input_power_health.as_str()),
+        (Field::AuxCurrent, w.aux_current_health.as_str()),
+        (Field::PexCurrent, w.pex_current_health.as_str()),
+        (Field::Aux12V, w.aux_12v_health.as_str()),
+        (Field::Pex12V, w.pex_12v_health.as_str()),
+        (Field::VccintCurrent, w.vccint_current_health.as_str()),
+        (Field::VrCtrl, w.vrctrl_temp_health.as_str()),
+        (Field::VrPower, w.phase0_temperature_health.as_str()),
+    ];
+    if let Some(core) = w.cores.cores.first() {
+        fields.push((Field::Clock, core.clock.health.as_str()));
+    }
+    for (n, sysmon) in w.sysmons.sysmon.iter().enumerate() {
+        fields.push((Field::Sysmon(n), sysmon.health.as_str()));
+    }
+    // devices without a sensor report something other than a level for it
+    fields.retain(|(_, level)| LEVELS.contains(level));
+    fields
+}
+
+//
+// Health residency of every field of every device for the session, keyed by
+// hw_uid like the history.
+//
+#[derive(Default)]
+pub struct Tracker {
+    devices: HashMap<String, BTreeMap<Field, Residency>>,
+}
+
+impl Tracker {
+    pub fn record_workers(&mut self, time: SystemTime, workers: &[webdata::Worker]) {
+        for w in workers {
+            let device = self.devices.entry(w.hw_uid.clone()).or_default();
+            for (field, level) in fields(w) {
+                device.entry(field).or_default().record(time, level);
+            }
+        }
+    }
+
+    pub fn device(&self, hw_uid: &str) -> Option<&BTreeMap<Field, Residency>> {
+        self.devices.get(hw_uid)
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+use crate::webdata;
+
+pub const DEFAULT_RETENTION_SECS: u64 = 3600;
+pub const DEFAULT_CAPACITY: usize = 3600;
+// samples past the retention are averaged down to one a minute and kept this
+// long, for the longest chart window
+pub const DOWNSAMPLE_SECS: u64 = 60;
+pub const DOWNSAMPLED_RETENTION_SECS: u64 = 24 * 60 * 60;
+
+//
+// Everything we keep a time series of for each device.  Rates are per second over
+// the last minute; Hashrate and AcceptedRate are the device's own, the pool rates
+// are keyed by the stats name minerator reports for the worksource or fee.
+//
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Metric {
+    InputPower,
+    Aux12V,
+    AuxCurrent,
+    Pex12V,
+    PexCurrent,
+    Vccint,
+    VccintCurrent,
+    VrctrlTemp,
+    Phase0Temperature,
+    Phase0Vout,
+    Phase1Temperature,
+    Phase1Vout,
+    SysmonTemperature(usize),
+    SysmonVccint(usize),
+    SysmonVccaux(usize),
+    SysmonVccbram(usize),
+    Multiplier,
+    BadNonces,
+    TotalNonces,
+    Hashrate,
+    AcceptedRate,
+    Calculated(String),
+    Accepted(String),
+}
+
+impl Metric {
+    //
+    // Whether adding the metric up across devices means something, as opposed
+    // to averaging it.
+    //
+    pub fn is_additive(&self) -> bool {
+        matches!(
+            self,
+            Metric::InputPower
+                | Metric::AuxCurrent
+                | Metric::PexCurrent
+                | Metric::VccintCurrent
+                | Metric::BadNonces
+                | Metric::TotalNonces
+                | Metric::Hashrate
+                | Metric::AcceptedRate
+                | Metric::Calculated(_)
+                | Metric::Accepted(_)
+        )
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Metric::InputPower => "Input Power".to_string(),
+            Metric::Aux12V => "AUX 12V".to_string(),
+            Metric::AuxCurrent => "AUX Current".to_string(),
+            Metric::Pex12V => "PEX 12V".to_string(),
+            Metric::PexCurrent => "PEX Current".to_string(),
+            Metric::Vccint => "VCCINT".to_string(),
+            Metric::VccintCurrent => "VCCINT Current".to_string(),
+            Metric::VrctrlTemp => "VRCTRL Temp".to_string(),
+            Metric::Phase0Temperature => "Phase 0 temperature".to_string(),
+            Metric::Phase0Vout => "Phase 0 vout".to_string(),
+            Metric::Phase1Temperature => "Phase 1 temperature".to_string(),
+            Metric::Phase1Vout => "Phase 1 vout".to_string(),
+            Metric::SysmonTemperature(n) => format!("Sysmon {} temp", n),
+            Metric::SysmonVccint(n) => format!("Sysmon {} vccint", n),
+            Metric::SysmonVccaux(n) => format!("Sysmon {} vccaux", n),
+            Metric::SysmonVccbram(n) => format!("Sysmon {} vccbram", n),
+            Metric::Multiplier => "Clock Multiplier".to_string(),
+            Metric::BadNonces => "Bad Nonces".to_string(),
+            Metric::TotalNonces => "Total Nonces".to_string(),
+            Metric::Hashrate => "Hashrate".to_string(),
+            Metric::AcceptedRate => "Accepted rate".to_string(),
+            Metric::Calculated(name) => format!("Calculated {}", name),
+            Metric::Accepted(name) => format!("Accepted {}", name),
+        }
+    }
+}
+
+//
+// The current value of every metric of a worker.  The phases and vrctrl only
+// exist on BCUs.
+//
+pub fn samples(w: &webdata::Worker) -> Vec<(Metric, f64)> {
+    let mut samples = vec![
+        (Metric::InputPower, w.input_power.into()),
+        (Metric::Aux12V, w.aux_12v.into()),
+        (Metric::AuxCurrent, w.aux_current.into()),
+        (Metric::Pex12V, w.pex_12v.into()),
+        (Metric::PexCurrent, w.pex_current.into()),
+        (Metric::Vccint, w.vccint.into()),
+        (Metric::VccintCurrent, w.vccint_current.into()),
+    ];
+    if w.hw_type == webdata::HWTYPE_BCU {
+        samples.push((Metric::VrctrlTemp, w.vrctrl_temp.into()));
+        samples.push((Metric::Phase0Temperature, w.phase0_temperature.into()));
+        samples.push((Metric::Phase0Vout, w.phase0_vout.into()));
+        samples.push((Metric::Phase1Temperature, w.phase1_temperature.into()));
+        samples.push((Metric::Phase1Vout, w.phase1_vout.into()));
+    }
+    for (num, sysmon) in w.sysmons.sysmon.iter().enumerate() {
+        samples.push((Metric::SysmonTemperature(num), sysmon.temperature.into()));
+        samples.push((Metric::SysmonVccint(num), sysmon.vccint.into()));
+        samples.push((Metric::SysmonVccaux(num), sysmon.vccaux.into()));
+        samples.push((Metric::SysmonVccbram(num), sysmon.vccbram.into()));
+    }
+    // one sample per device, the cores' counts and rates added up and their
+    // multipliers averaged
+    if !w.cores.cores.is_empty() {
+        let (mut multiplier, mut bad, mut total, mut calculated, mut accepted) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for core in &w.cores.cores {
+            multiplier += f64::from(core.clock.multiplier);
+            bad += f64::from(core.clock.badNonces);
+            total += f64::from(core.clock.totalNonces);
+            calculated += f64::from(core.stats.minute.calculated);
+            accepted += f64::from(core.stats.minute.accepted);
+        }
+        samples.push((Metric::Multiplier, multiplier / w.cores.cores.len() as f64));
+        samples.push((Metric::BadNonces, bad));
+        samples.push((Metric::TotalNonces, total));
+        samples.push((Metric::Hashrate, calculated / 60.0));
+        samples.push((Metric::AcceptedRate, accepted / 60.0));
+    }
+    for stats in &[&w.worksource.stats, &w.fee.stats] {
+        let calculated = f64::from(stats.minute.calculated) / 60.0;
+        let accepted = f64::from(stats.minute.accepted) / 60.0;
+        samples.push((Metric::Calculated(stats.name.clone()), calculated));
+        samples.push((Metric::Accepted(stats.name.clone()), accepted));
+    }
+    samples
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub time: SystemTime,
+    pub value: f64,
+}
+
+//
+// One metric's samples, each one kept while it's recent and then averaged into
+// the minute it was taken in, along with how many went into each minute.
+//
+#[derive(Debug, Default)]
+struct Series {
+    recent: VecDeque<Sample>,
+    minutes: VecDeque<(Sample, u32)>,
+}
+
+impl Series {
+    fn downsample(&mut self, sample: Sample) {
+        let secs = sample
+            .time
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(secs - secs % DOWNSAMPLE_SECS);
+        match self.minutes.back_mut() {
+            Some((minute, count)) if minute.time == time => {
+                minute.value = (minute.value * f64::from(*count) + sample.value) / f64::from(*count + 1);
+                *count += 1;
+            }
+            _ => self.minutes.push_back((Sample { time, ..sample }, 1)),
+        }
+    }
+}
+
+//
+// Bounded store of samples per device (by hwUID) and metric.  Samples older than
+// the retention, or the oldest once a series reaches capacity, are averaged into
+// a sample a minute that is dropped after DOWNSAMPLED_RETENTION_SECS.
+//
+pub struct History {
+    retention: Duration,
+    capacity: usize,
+    devices: HashMap<String, BTreeMap<Metric, Series>>,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(DEFAULT_RETENTION_SECS), DEFAULT_CAPACITY)
+    }
+}
+
+impl History {
+    pub fn new(retention: Duration, capacity: usize) -> History {
+        History {
+            retention,
+            capacity: capacity.max(1),
+            devices: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, hw_uid: &str, metric: Metric, time: SystemTime, value: f64) {
+        let series = self
+            .devices
+            .entry(hw_uid.to_string())
+            .or_default()
+            .entry(metric)
+            .or_default();
+        series.recent.push_back(Sample { time, value });
+        while series.recent.len() > self.capacity {
+            if let Some(oldest) = series.recent.pop_front() {
+                series.downsample(oldest);
+            }
+        }
+    }
+
+    //
+    // Adds a sample of every metric for each worker, then forgets anything that
+    // has aged out, including devices that have gone away.
+    //
+    pub fn record_workers(&mut self, time: SystemTime, workers: &[webdata::Worker]) {
+        for w in workers {
+            for (metric, value) in samples(w) {
+                self.record(&w.hw_uid, metric, time, value);
+            }
+        }
+        self.expire(time);
+    }
+
+    pub fn expire(&mut self, now: SystemTime) {
+        let cutoff = now.checked_sub(self.retention).unwrap_or(SystemTime::UNIX_EPOCH);
+        let long_cutoff = now
+            .checked_sub(Duration::from_secs(DOWNSAMPLED_RETENTION_SECS))
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        for metrics in self.devices.values_mut() {
+            for series in metrics.values_mut() {
+                while let Some(oldest) = series.recent.front().cloned() {
+                    if oldest.time >= cutoff {
+                        break;
+                    }
+                    series.recent.pop_front();
+                    series.downsample(oldest);
+                }
+                while matches!(series.minutes.front(), Some((minute, _)) if minute.time < long_cutoff) {
+                    series.minutes.pop_front();
+                }
+            }
+            metrics.retain(|_, series| !series.recent.is_empty() || !series.minutes.is_empty());
+        }
+        self.devices.retain(|_, metrics| !metrics.is_empty());
+    }
+
+    //
+    // The samples of one metric still kept in full.
+    //
+    pub fn series(&self, hw_uid: &str, metric: &Metric) -> Option<&VecDeque<Sample>> {
+        self.devices.get(hw_uid)?.get(metric).map(|series| &series.recent)
+    }
+
+    //
+    // Samples of one metric taken at or after start, oldest first, with a
+    // minute's average standing in for those no longer kept in full.
+    //
+    pub fn since(&self, hw_uid: &str, metric: &Metric, start: SystemTime) -> Vec<Sample> {
+        let series = match self.devices.get(hw_uid).and_then(|metrics| metrics.get(metric)) {
+            Some(series) => series,
+            None => return vec![],
+        };
+        let first_recent = series.recent.front().map(|s| s.time);
+        series
+            .minutes
+            .iter()
+            .map(|(minute, _)| minute)
+            .filter(|minute| minute.time >= start && !matches!(first_recent, Some(t) if minute.time >= t))
+            .chain(series.recent.iter().filter(|s| s.time >= start))
+            .cloned()
+            .collect()
+    }
+
+    pub fn metrics(&self, hw_uid: &str) -> Vec<Metric> {
+        match self.devices.get(hw_uid) {
+            Some(metrics) => metrics.keys().cloned().collect(),
+            None => vec![],
+        }
+    }
+}
@@ -0,0 +1,383 @@
+use log::*;
+use pancurses::Input;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+//
+// Everything a key can do outside of the device numbers and the keys that
+// only mean something inside the chart and fleet views.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    Quit,
+    NextDevice,
+    PreviousDevice,
+    NextPage,
+    PreviousPage,
+    LogPageNewer,
+    LogPageOlder,
+    Search,
+    LogSearch,
+    NextMatch,
+    PreviousMatch,
+    Follow,
+    LogFilter,
+    LogRegex,
+    TogglePane,
+    TogglePanel,
+    Zoom,
+    Pause,
+    Help,
+    Sparklines,
+    StatsMode,
+    Chart,
+    Health,
+    Session,
+    Fleet,
+    ResetSession,
+    OlderEvents,
+    NewerEvents,
+}
+
+pub const ACTIONS: [Action; 28] = [
+    Action::Quit,
+    Action::NextDevice,
+    Action::PreviousDevice,
+    Action::NextPage,
+    Action::PreviousPage,
+    Action::LogPageNewer,
+    Action::LogPageOlder,
+    Action::Search,
+    Action::LogSearch,
+    Action::NextMatch,
+    Action::PreviousMatch,
+    Action::Follow,
+    Action::LogFilter,
+    Action::LogRegex,
+    Action::TogglePane,
+    Action::TogglePanel,
+    Action::Zoom,
+    Action::Pause,
+    Action::Help,
+    Action::Sparklines,
+    Action::StatsMode,
+    Action::Chart,
+    Action::Health,
+    Action::Session,
+    Action::Fleet,
+    Action::ResetSession,
+    Action::OlderEvents,
+    Action::NewerEvents,
+];
+
+impl Action {
+    //
+    // The name the config file uses for the action.
+    //
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::NextDevice => "next_device",
+            Action::PreviousDevice => "previous_device",
+            Action::NextPage => "next_page",
+            Action::PreviousPage => "previous_page",
+            Action::LogPageNewer => "log_page_newer",
+            Action::LogPageOlder => "log_page_older",
+            Action::Search => "search",
+            Action::LogSearch => "log_search",
+            Action::NextMatch => "next_match",
+            Action::PreviousMatch => "previous_match",
+            Action::Follow => "follow",
+            Action::LogFilter => "log_filter",
+            Action::LogRegex => "log_regex",
+            Action::TogglePane => "toggle_pane",
+            Action::TogglePanel => "toggle_panel",
+            Action::Zoom => "zoom",
+            Action::Pause => "pause",
+            Action::Help => "help",
+            Action::Sparklines => "sparklines",
+            Action::StatsMode => "stats_mode",
+            Action::Chart => "chart",
+            Action::Health => "health",
+            Action::Session => "session",
+            Action::Fleet => "fleet",
+            Action::ResetSession => "reset_session",
+            Action::OlderEvents => "older_events",
+            Action::NewerEvents => "newer_events",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "exit viewerator",
+            Action::NextDevice => "show the next device",
+            Action::PreviousDevice => "show the previous device",
+            Action::NextPage => "log page newer, or devices forward",
+            Action::PreviousPage => "log page older, or devices back",
+            Action::LogPageNewer => "log page newer",
+            Action::LogPageOlder => "log page older",
+            Action::Search => "find device by number, name, hwUID",
+            Action::LogSearch => "search the log as you type",
+            Action::NextMatch => "older log match",
+            Action::PreviousMatch => "newer log match",
+            Action::Follow => "follow the log or pause it",
+            Action::LogFilter => "device, all, warning or error log",
+            Action::LogRegex => "log lines matching a regex",
+            Action::TogglePane => "show or hide the event pane",
+            Action::TogglePanel => "show or hide a panel by name",
+            Action::Zoom => "zoom a panel, then the next",
+            Action::Pause => "pause or resume polling minerator",
+            Action::Help => "show the keys",
+            Action::Sparklines => "show or hide sparklines",
+            Action::StatsMode => "stats as rates, efficiency, counts",
+            Action::Chart => "chart view",
+            Action::Health => "health residency view",
+            Action::Session => "session min/max beside each value",
+            Action::Fleet => "fleet table view",
+            Action::ResetSession => "reset session min/max",
+            Action::OlderEvents => "scroll the event pane back",
+            Action::NewerEvents => "scroll the event pane forward",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Action> {
+        ACTIONS.iter().find(|a| a.name() == name).cloned()
+    }
+}
+
+const NAMED_KEYS: [(&str, Input); 17] = [
+    ("Space", Input::Character(' ')),
+    ("Tab", Input::Character('\t')),
+    ("Enter", Input::Character('\n')),
+    ("Escape", Input::Character('\u{1b}')),
+    ("Delete", Input::KeyDC),
+    ("Backspace", Input::KeyBackspace),
+    ("Insert", Input::KeyIC),
+    ("Left", Input::KeyLeft),
+    ("Right", Input::KeyRight),
+    ("Up", Input::KeyUp),
+    ("Down", Input::KeyDown),
+    ("BackTab", Input::KeyBTab),
+    ("PageUp", Input::KeyPPage),
+    ("PageDown", Input::KeyNPage),
+    ("Shift-PageUp", Input::KeySPrevious),
+    ("Shift-PageDown", Input::KeySNext),
+    ("Home", Input::KeyHome),
+];
+
+//
+// A key from its config file name: a single character, one of NAMED_KEYS in
+// any case, End, F1 to F12, or Ctrl- followed by a letter.
+//
+pub fn parse_key(name: &str) -> Option<Input> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Input::Character(c));
+    }
+    let lower = name.to_lowercase();
+    if let Some((_, input)) = NAMED_KEYS.iter().find(|(n, _)| n.to_lowercase() == lower) {
+        return Some(*input);
+    }
+    if lower == "end" {
+        return Some(Input::KeyEnd);
+    }
+    if let Some(letter) = lower.strip_prefix("ctrl-") {
+        let mut chars = letter.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_lowercase() => Some(Input::Character((c as u8 & 0x1f) as char)),
+            _ => None,
+        };
+    }
+    let f: u8 = lower.strip_prefix('f')?.parse().ok()?;
+    Some(match f {
+        1 => Input::KeyF1,
+        2 => Input::KeyF2,
+        3 => Input::KeyF3,
+        4 => Input::KeyF4,
+        5 => Input::KeyF5,
+        6 => Input::KeyF6,
+        7 => Input::KeyF7,
+        8 => Input::KeyF8,
+        9 => Input::KeyF9,
+        10 => Input::KeyF10,
+        11 => Input::KeyF11,
+        12 => Input::KeyF12,
+        _ => return None,
+    })
+}
+
+//
+// The name parse_key reads a key back from, for showing the bindings.
+//
+pub fn key_name(input: &Input) -> String {
+    if let Some((name, _)) = NAMED_KEYS.iter().find(|(_, i)| i == input) {
+        return name.to_string();
+    }
+    match input {
+        Input::Character(c) if (*c as u32) < 0x20 => format!("Ctrl-{}", ((*c as u8) | 0x60) as char),
+        Input::Character(c) => c.to_string(),
+        Input::KeyEnd => "End".to_string(),
+        other => format!("{:?}", other).replace("Key", ""),
+    }
+}
+
+//
+// Which preset to start from and the keys to use instead of the preset's for
+// any action, as the config file has them, like
+//   "keys": { "preset": "vim", "bindings": { "quit": ["q", "Ctrl-c"] } }
+//
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Bindings {
+    pub preset: String,
+    pub bindings: BTreeMap<String, Vec<String>>,
+}
+
+pub const PRESETS: [&str; 3] = ["default", "vim", "top"];
+
+fn preset(name: &str) -> Option<Vec<(&'static str, Action)>> {
+    let mut keys = vec![
+        ("Delete", Action::Quit),
+        ("q", Action::Quit),
+        ("Right", Action::NextDevice),
+        ("Down", Action::NextDevice),
+        ("Tab", Action::NextDevice),
+        ("Left", Action::PreviousDevice),
+        ("Up", Action::PreviousDevice),
+        ("BackTab", Action::PreviousDevice),
+        ("PageDown", Action::NextPage),
+        ("PageUp", Action::PreviousPage),
+        // keys that only ever page the log, whatever the view does with pages
+        ("d", Action::LogPageNewer),
+        ("u", Action::LogPageOlder),
+        ("Shift-PageDown", Action::LogPageNewer),
+        ("Shift-PageUp", Action::LogPageOlder),
+        ("g", Action::Search),
+        ("/", Action::LogSearch),
+        ("n", Action::NextMatch),
+        ("N", Action::PreviousMatch),
+        ("f", Action::Follow),
+        ("L", Action::LogFilter),
+        ("&", Action::LogRegex),
+        ("p", Action::TogglePane),
+        ("P", Action::TogglePanel),
+        ("z", Action::Zoom),
+        ("Space", Action::Pause),
+        ("?", Action::Help),
+        ("s", Action::Sparklines),
+        ("e", Action::StatsMode),
+        ("c", Action::Chart),
+        ("h", Action::Health),
+        ("x", Action::Session),
+        ("t", Action::Fleet),
+        ("r", Action::ResetSession),
+        ("[", Action::OlderEvents),
+        ("]", Action::NewerEvents),
+    ];
+    let mut rebind = |overrides: &[(&'static str, Action)]| {
+        for (key, action) in overrides {
+            keys.retain(|(k, _)| k != key);
+            keys.push((key, *action));
+        }
+    };
+    match name {
+        "" | "default" => {}
+        // hjkl move between devices, so the health view moves to H
+        "vim" => rebind(&[
+            ("h", Action::PreviousDevice),
+            ("k", Action::PreviousDevice),
+            ("l", Action::NextDevice),
+            ("j", Action::NextDevice),
+            ("Ctrl-b", Action::PreviousPage),
+            ("Ctrl-f", Action::NextPage),
+            ("H", Action::Health),
+        ]),
+        // top's h is help, so the health view moves to H
+        "top" => rebind(&[
+            ("h", Action::Help),
+            ("H", Action::Health),
+            ("Z", Action::Pause),
+            ("W", Action::Sparklines),
+            ("F1", Action::Help),
+        ]),
+        _ => return None,
+    }
+    Some(keys)
+}
+
+//
+// The action each key does, from a preset with the config's bindings on top.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keymap {
+    keys: Vec<(Input, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&Bindings::default())
+    }
+}
+
+impl Keymap {
+    //
+    // Anything the config gets wrong is logged and left at the preset.
+    //
+    pub fn new(bindings: &Bindings) -> Keymap {
+        let keys = match preset(&bindings.preset) {
+            Some(keys) => keys,
+            None => {
+                warn!("Unknown key preset {}, using default", bindings.preset);
+                preset("default").unwrap_or_default()
+            }
+        };
+        let mut keymap = Keymap {
+            keys: keys
+                .iter()
+                .filter_map(|(name, action)| parse_key(name).map(|input| (input, *action)))
+                .collect(),
+        };
+        for (name, keys) in &bindings.bindings {
+            let action = match Action::parse(name) {
+                Some(action) => action,
+                None => {
+                    warn!("Unknown action {} in key bindings", name);
+                    continue;
+                }
+            };
+            let inputs: Vec<Input> = keys
+                .iter()
+                .filter_map(|key| {
+                    let input = parse_key(key);
+                    if input.is_none() {
+                        warn!("Unknown key {} bound to {}", key, name);
+                    }
+                    input
+                })
+                .collect();
+            keymap.keys.retain(|(input, a)| *a != action && !inputs.contains(input));
+            keymap.keys.extend(inputs.into_iter().map(|input| (input, action)));
+        }
+        keymap
+    }
+
+    pub fn action(&self, input: &Input) -> Option<Action> {
+        self.keys.iter().find(|(i, _)| i == input).map(|(_, action)| *action)
+    }
+
+    pub fn keys(&self, action: Action) -> Vec<Input> {
+        self.keys
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(input, _)| *input)
+            .collect()
+    }
+
+    //
+    // The keys bound to action by name, separated by slashes.
+    //
+    pub fn describe(&self, action: Action) -> String {
+        let names: Vec<String> = self.keys(action).iter().map(key_name).collect();
+        names.join("/")
+    }
+}
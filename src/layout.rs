@@ -0,0 +1,409 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// the classic layout needs this much room, anything smaller is compact
+pub const WIDE_WIDTH: i32 = 126;
+pub const WIDE_HEIGHT: i32 = 25;
+// rows 0-3 are the header and the device being shown
+pub const BODY_TOP: i32 = 4;
+pub const STATS_WIDE_WIDTH: i32 = 126;
+pub const STATS_COMPACT_WIDTH: i32 = 76;
+pub const EVENTS_WIDTH: i32 = 48;
+const STATS_HEIGHT: i32 = 9;
+const REGULATOR_WIDTH: i32 = 24;
+const REGULATOR_HEIGHT: i32 = 6;
+const ENERGY_WIDTH: i32 = 70;
+const SUMMARY_WIDTH: i32 = 76;
+const COMPACT_EVENTS_HEIGHT: i32 = 6;
+const MIN_LOG_HEIGHT: i32 = 2;
+const GAP: i32 = 2;
+pub const HELP_WIDTH: i32 = 112;
+// a 16 column key and its description, two to a 126 column terminal
+pub const HELP_COLUMN_WIDTH: i32 = 52;
+
+//
+// The panels of the device view, in the order the compact layout gives them
+// room.
+//
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Panel {
+    Sensors,
+    Clock,
+    Stats,
+    Sysmons,
+    Phases,
+    Regulator,
+    Energy,
+    Summary,
+    Events,
+    Log,
+}
+
+pub const PANELS: [Panel; 10] = [
+    Panel::Sensors,
+    Panel::Clock,
+    Panel::Stats,
+    Panel::Sysmons,
+    Panel::Phases,
+    Panel::Regulator,
+    Panel::Energy,
+    Panel::Summary,
+    Panel::Events,
+    Panel::Log,
+];
+
+// the panels one key zooms through, in order
+pub const ZOOMABLE: [Panel; 5] = [Panel::Log, Panel::Stats, Panel::Sysmons, Panel::Phases, Panel::Events];
+
+impl Panel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Panel::Sensors => "sensors",
+            Panel::Clock => "clock",
+            Panel::Stats => "stats",
+            Panel::Sysmons => "sysmons",
+            Panel::Phases => "phases",
+            Panel::Regulator => "regulator",
+            Panel::Energy => "energy",
+            Panel::Summary => "summary",
+            Panel::Events => "events",
+            Panel::Log => "log",
+        }
+    }
+
+    //
+    // The first panel whose name starts with what was typed, ignoring case.
+    //
+    pub fn parse(name: &str) -> Option<Panel> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+        PANELS.iter().find(|p| p.name().starts_with(&name)).cloned()
+    }
+}
+
+//
+// Which panels of the device view are hidden and which, if any, has the whole
+// body to itself, as the config file has them, like
+//   "layout": { "hidden": ["energy", "summary"], "zoom": "log" }
+//
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Preferences {
+    pub hidden: Vec<Panel>,
+    pub zoom: Option<Panel>,
+}
+
+impl Preferences {
+    //
+    // Hides the panel if it's showing and shows it if it's hidden, true when it
+    // ends up showing.
+    //
+    pub fn toggle(&mut self, panel: Panel) -> bool {
+        if self.hidden.contains(&panel) {
+            self.hidden.retain(|p| *p != panel);
+            true
+        } else {
+            self.hidden.push(panel);
+            false
+        }
+    }
+
+    //
+    // Zooms the next of ZOOMABLE after the one zoomed, back to no zoom after
+    // the last.  Only BCUs have phases to zoom.
+    //
+    pub fn next_zoom(&mut self, bcu: bool) -> Option<Panel> {
+        let start = match self.zoom {
+            Some(zoom) => ZOOMABLE.iter().position(|p| *p == zoom).map_or(0, |i| i + 1),
+            None => 0,
+        };
+        self.zoom = ZOOMABLE[start..].iter().find(|p| bcu || **p != Panel::Phases).cloned();
+        self.zoom
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub y: i32,
+    pub x: i32,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl Rect {
+    pub fn new(y: i32, x: i32, height: i32, width: i32) -> Rect {
+        Rect { y, x, height, width }
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    pub fn contains(&self, y: i32, x: i32) -> bool {
+        y >= self.y && y < self.bottom() && x >= self.x && x < self.right()
+    }
+}
+
+//
+// What the device being shown needs room for.  sparkline_width is how much the
+// sparklines, or the session columns in their place, widen each panel, 0 when
+// both are off.  Hidden panels give their room to the log, and a zoomed one gets
+// the whole body.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Content {
+    pub bcu: bool,
+    pub sparkline_width: i32,
+    pub sysmons: usize,
+    pub hidden: Vec<Panel>,
+    pub zoom: Option<Panel>,
+}
+
+impl Content {
+    fn shows(&self, panel: Panel) -> bool {
+        !self.hidden.contains(&panel) && (panel != Panel::Phases || self.bcu)
+    }
+
+    fn size(&self, panel: Panel) -> (i32, i32) {
+        let sw = self.sparkline_width;
+        match panel {
+            Panel::Sensors => (24 + sw, 10),
+            Panel::Clock => (38 + sw, 4),
+            Panel::Stats => (STATS_COMPACT_WIDTH, STATS_HEIGHT),
+            Panel::Sysmons => (16 * self.sysmons as i32, if sw > 0 { 6 } else { 4 }),
+            Panel::Phases => (22 + sw, 9),
+            Panel::Regulator => (REGULATOR_WIDTH, REGULATOR_HEIGHT),
+            Panel::Energy => (ENERGY_WIDTH, 1),
+            Panel::Summary => (SUMMARY_WIDTH, 2),
+            Panel::Events => (EVENTS_WIDTH, COMPACT_EVENTS_HEIGHT),
+            Panel::Log => (0, 0),
+        }
+    }
+}
+
+//
+// Where each panel of the device view goes for a terminal size.  Terminals big
+// enough get the classic arrangement, with any extra room going to the log.
+// Smaller ones, or ones too narrow for the sysmons, get the panels flowed into
+// rows, most important first, and any that don't fit are left out.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+    pub width: i32,
+    pub height: i32,
+    pub compact: bool,
+    // the panel given the whole body, which draws more than at its usual size
+    pub zoomed: Option<Panel>,
+    panels: BTreeMap<Panel, Rect>,
+}
+
+impl Layout {
+    pub fn new(width: i32, height: i32, content: &Content) -> Layout {
+        let mut layout = Layout {
+            width: width.max(0),
+            height: height.max(0),
+            compact: width < WIDE_WIDTH || height < WIDE_HEIGHT,
+            zoomed: None,
+            panels: BTreeMap::new(),
+        };
+        let zoom = content
+            .zoom
+            .filter(|p| ZOOMABLE.contains(p) && (*p != Panel::Phases || content.bcu));
+        if let Some(panel) = zoom {
+            if layout.height > BODY_TOP {
+                let rect = Rect::new(BODY_TOP, 0, layout.height - BODY_TOP, layout.width);
+                layout.panels.insert(panel, rect);
+                layout.zoomed = Some(panel);
+            }
+        } else if layout.compact {
+            layout.flow(content);
+        } else {
+            layout.wide(content);
+            // more sysmons than the classic arrangement has columns for
+            if layout.panels.values().any(|r| r.right() > layout.width) {
+                layout.panels.clear();
+                layout.compact = true;
+                layout.flow(content);
+            }
+        }
+        layout
+    }
+
+    pub fn get(&self, panel: Panel) -> Option<Rect> {
+        self.panels.get(&panel).cloned()
+    }
+
+    fn wide(&mut self, content: &Content) {
+        let sw = content.sparkline_width;
+        let (width, height) = (self.width, self.height);
+        let size = |panel| content.size(panel);
+        self.panels.insert(Panel::Summary, Rect::new(2, 50, 2, width - 50));
+        let (w, h) = size(Panel::Sensors);
+        self.panels.insert(Panel::Sensors, Rect::new(BODY_TOP, 0, h, w));
+        // sparklines widen the panels to the left, the phases only exist on BCUs
+        let mut right = 50 + sw;
+        if content.bcu {
+            let (w, h) = size(Panel::Phases);
+            self.panels.insert(Panel::Phases, Rect::new(BODY_TOP, 26 + sw, h, w));
+            right += sw;
+        }
+        let (w, h) = size(Panel::Clock);
+        let clock = Rect::new(BODY_TOP, right, h, w);
+        self.panels.insert(Panel::Clock, clock);
+        let (w, h) = size(Panel::Sysmons);
+        let sysmons = Rect::new(clock.bottom() + if sw > 0 { 0 } else { 1 }, right, h, w);
+        self.panels.insert(Panel::Sysmons, sysmons);
+        // the regulator goes right of the clock, and of the sysmons it would reach
+        // down to, when there is room
+        let regulator_x = clock.right().max(sysmons.right());
+        if regulator_x + REGULATOR_WIDTH <= width {
+            let regulator = Rect::new(BODY_TOP, regulator_x, REGULATOR_HEIGHT, REGULATOR_WIDTH);
+            self.panels.insert(Panel::Regulator, regulator);
+        }
+        // the energy line shares the row under the phases with the sysmon sparklines
+        let energy_x = 26 + sw;
+        let energy_right = if sysmons.bottom() > 13 { right - 1 } else { width };
+        self.panels
+            .insert(Panel::Energy, Rect::new(13, energy_x, 1, energy_right - energy_x));
+        self.panels
+            .insert(Panel::Stats, Rect::new(14, 0, STATS_HEIGHT, STATS_WIDE_WIDTH));
+        self.panels.retain(|panel, _| content.shows(*panel));
+        // the log and events move up into whatever hidden panels left empty
+        let bottom = self.panels.values().map(|r| r.bottom()).fold(BODY_TOP, i32::max);
+        let mut log_width = width;
+        if content.shows(Panel::Events) {
+            self.panels.insert(
+                Panel::Events,
+                Rect::new(bottom, width - EVENTS_WIDTH, height - bottom, EVENTS_WIDTH),
+            );
+            log_width -= EVENTS_WIDTH + 1;
+        }
+        if content.shows(Panel::Log) {
+            self.panels
+                .insert(Panel::Log, Rect::new(bottom, 0, height - bottom, log_width));
+        }
+    }
+
+    //
+    // Places the panels left to right in rows, starting a new row when the
+    // next doesn't fit beside the last, then gives the log what is left.
+    //
+    fn flow(&mut self, content: &Content) {
+        let mut row_y = BODY_TOP;
+        let mut row_x = 0;
+        let mut row_height = 0;
+        let panels = [
+            Panel::Sensors,
+            Panel::Clock,
+            Panel::Stats,
+            Panel::Sysmons,
+            Panel::Phases,
+            Panel::Regulator,
+            Panel::Energy,
+            Panel::Summary,
+            Panel::Events,
+        ];
+        for panel in panels.iter() {
+            if !content.shows(*panel) {
+                continue;
+            }
+            let (w, h) = if *panel == Panel::Stats && self.width >= STATS_WIDE_WIDTH {
+                (STATS_WIDE_WIDTH, STATS_HEIGHT)
+            } else {
+                content.size(*panel)
+            };
+            // these clip what they show to the room they get, the rest need all of theirs
+            let w = if matches!(panel, Panel::Energy | Panel::Events) {
+                w.min(self.width)
+            } else {
+                w
+            };
+            if w <= 0 || w > self.width {
+                continue;
+            }
+            if row_x > 0 && row_x + w > self.width {
+                row_y += row_height;
+                row_x = 0;
+                row_height = 0;
+            }
+            if row_y + h > self.height {
+                continue;
+            }
+            self.panels.insert(*panel, Rect::new(row_y, row_x, h, w));
+            row_x += w + GAP;
+            row_height = row_height.max(h);
+        }
+        let log_y = row_y + row_height;
+        if content.shows(Panel::Log) && self.height - log_y >= MIN_LOG_HEIGHT {
+            self.panels
+                .insert(Panel::Log, Rect::new(log_y, 0, self.height - log_y, self.width));
+        }
+    }
+}
+
+//
+// The help overlay's box, centred over the screen with a margin, or None when
+// the screen is too small for one.
+//
+pub fn help_box(width: i32, height: i32) -> Option<Rect> {
+    let (box_width, box_height) = ((width - 4).min(HELP_WIDTH), height - 2);
+    if box_width < 20 || box_height < 5 {
+        return None;
+    }
+    Some(Rect::new(
+        (height - box_height) / 2,
+        (width - box_width) / 2,
+        box_height,
+        box_width,
+    ))
+}
+
+//
+// Lines flowed down and then across as many columns as fit inside a box, and
+// scrolled a line at a time when there are more than fit at once.
+//
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Columns {
+    pub count: usize,
+    pub rows: usize,
+    pub width: i32,
+}
+
+impl Columns {
+    //
+    // Columns of at most column_width inside a box, less its border and margin.
+    //
+    pub fn new(r: Rect, column_width: i32) -> Columns {
+        let inside = (r.width - 4).max(1);
+        let width = column_width.min(inside);
+        Columns {
+            count: ((inside + GAP) / (width + GAP)).max(1) as usize,
+            rows: (r.height - 2).max(1) as usize,
+            width,
+        }
+    }
+
+    pub fn max_scroll(&self, lines: usize) -> usize {
+        lines.saturating_sub(self.count * self.rows)
+    }
+
+    //
+    // Where in the box line goes when scrolled by scroll, as the row and column
+    // inside the border, None when it isn't showing.
+    //
+    pub fn place(&self, line: usize, scroll: usize) -> Option<(i32, i32)> {
+        let shown = line.checked_sub(scroll)?;
+        let column = shown / self.rows;
+        if column >= self.count {
+            return None;
+        }
+        Some(((shown % self.rows) as i32, column as i32 * (self.width + GAP)))
+    }
+}
@@ -1,5 +1,23 @@
 #![feature(process_exitcode_placeholder, termination_trait_lib)]
 
+pub mod chart;
+pub mod config;
+pub mod energy;
+pub mod events;
+pub mod fleet;
+pub mod health;
+pub mod history;
+pub mod keymap;
+pub mod layout;
 pub mod log_display;
+pub mod metrics;
+pub mod profit;
+pub mod regulator;
+pub mod scan;
 pub mod screen;
+pub mod session;
+pub mod snapshot;
+pub mod sparkline;
+pub mod theme;
+pub mod units;
 pub mod webdata;
@@ -5,6 +5,8 @@ use std::io::BufReader;
 use std::io::Seek;
 use std::io::SeekFrom;
 
+pub const LOG_FILE: &str = "/var/log/minerator.log";
+
 pub struct LogDisplay {
     fd: File,
 }
@@ -17,7 +19,7 @@ impl Default for LogDisplay {
 impl LogDisplay {
     pub fn new() -> LogDisplay {
         LogDisplay {
-            fd: File::open("/var/log/minerator.log").unwrap(),
+            fd: File::open(LOG_FILE).unwrap(),
         }
     }
 
@@ -34,7 +36,7 @@ impl LogDisplay {
         }
         for line in reader.lines().map(|l| l.unwrap_or_else(|_| String::from(""))) {
             if line.contains("Received SIGHUP") {
-                self.fd = File::open("/var/log/minerator.log").unwrap();
+                self.fd = File::open(LOG_FILE).unwrap();
                 info!("minerator log rolled, opened new one");
                 break;
             }
@@ -44,3 +46,234 @@ impl LogDisplay {
         raw
     }
 }
+
+//
+// The part of the log shown in the pane.  While following, each read replaces
+// the lines and the pane sticks to the newest.  Scrolling back or searching
+// pauses it so what's on screen holds still until it follows again.  scroll
+// counts lines back from the newest.
+//
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogView {
+    lines: Vec<String>,
+    pub scroll: usize,
+    pub follow: bool,
+    pub query: String,
+    // line of the match last jumped to
+    pub current: Option<usize>,
+    height: usize,
+}
+
+impl LogView {
+    pub fn new() -> LogView {
+        LogView {
+            follow: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn set_lines(&mut self, lines: Vec<String>) {
+        if self.follow || self.lines.is_empty() {
+            self.lines = lines;
+        }
+    }
+
+    //
+    // Starts over on the newest lines, for when the lines shown change
+    // meaning, keeping the search.
+    //
+    pub fn reset(&mut self) {
+        self.lines.clear();
+        self.follow();
+    }
+
+    pub fn follow(&mut self) {
+        self.follow = true;
+        self.scroll = 0;
+        self.current = None;
+    }
+
+    pub fn toggle_follow(&mut self) {
+        if self.follow {
+            self.follow = false;
+        } else {
+            self.follow();
+        }
+    }
+
+    //
+    // The lines that fit in height rows at the current scroll.
+    //
+    pub fn window(&mut self, height: usize) -> std::ops::Range<usize> {
+        self.height = height;
+        self.scroll = self.scroll.min(self.lines.len().saturating_sub(height));
+        let end = self.lines.len() - self.scroll;
+        end.saturating_sub(height)..end
+    }
+
+    //
+    // Moves back through older lines for a positive delta, pausing the view.
+    //
+    pub fn scroll_by(&mut self, delta: i64) {
+        if delta > 0 {
+            self.follow = false;
+            self.scroll += delta as usize;
+        } else {
+            self.scroll = self.scroll.saturating_sub((-delta) as usize);
+        }
+    }
+
+    pub fn page(&mut self, older: bool) {
+        let page = self.height.saturating_sub(1).max(1) as i64;
+        self.scroll_by(if older { page } else { -page });
+    }
+
+    //
+    // Searches back from the newest line, jumping to the newest match.  An
+    // empty query clears the search.
+    //
+    pub fn search(&mut self, query: &str) -> bool {
+        self.query = query.to_string();
+        self.current = None;
+        if query.is_empty() {
+            return false;
+        }
+        self.find(self.lines.len(), true)
+    }
+
+    //
+    // The next match older or newer than the current one.
+    //
+    pub fn next(&mut self, older: bool) -> bool {
+        if self.query.is_empty() {
+            return false;
+        }
+        let from = match self.current {
+            Some(current) if older => current,
+            Some(current) => current + 1,
+            None => self.lines.len(),
+        };
+        self.find(from, older)
+    }
+
+    fn find(&mut self, from: usize, older: bool) -> bool {
+        let found = if older {
+            (0..from.min(self.lines.len()))
+                .rev()
+                .find(|i| !highlights(&self.lines[*i], &self.query).is_empty())
+        } else {
+            (from..self.lines.len()).find(|i| !highlights(&self.lines[*i], &self.query).is_empty())
+        };
+        match found {
+            Some(i) => {
+                self.current = Some(i);
+                self.follow = false;
+                // middle of the pane, as far as the ends allow
+                self.scroll = (self.lines.len() - 1 - i).saturating_sub(self.height / 2);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn match_count(&self) -> usize {
+        if self.query.is_empty() {
+            return 0;
+        }
+        self.lines
+            .iter()
+            .filter(|line| !highlights(line, &self.query).is_empty())
+            .count()
+    }
+}
+
+//
+// Byte ranges of line matching query, ignoring ASCII case.
+//
+pub fn highlights(line: &str, query: &str) -> Vec<std::ops::Range<usize>> {
+    if query.is_empty() {
+        return vec![];
+    }
+    let haystack = line.to_ascii_lowercase();
+    let needle = query.to_ascii_lowercase();
+    let mut ranges = vec![];
+    let mut start = 0;
+    while let Some(found) = haystack[start..].find(&needle) {
+        let begin = start + found;
+        ranges.push(begin..begin + needle.len());
+        start = begin + needle.len();
+    }
+    ranges
+}
+
+//
+// The level minerator logs a line at, the word after its timestamp, as in
+// "[2020-02-14T21:36:36.589Z] WRN 0000:17:00.0:10000: Got bad nonce".
+//
+pub fn level(line: &str) -> Option<&str> {
+    line.split_whitespace().nth(1)
+}
+
+//
+// Which minerator log lines the pane shows.  Device is the lines naming the
+// board shown plus the dev fee ones.
+//
+#[derive(Clone, Debug, Default)]
+pub enum Filter {
+    All,
+    #[default]
+    Device,
+    Warnings,
+    Errors,
+    Regex(regex::Regex),
+}
+
+impl Filter {
+    //
+    // A filter for a user typed pattern, with regex's complaint if it isn't one.
+    //
+    pub fn regex(pattern: &str) -> Result<Filter, String> {
+        regex::Regex::new(pattern)
+            .map(Filter::Regex)
+            .map_err(|err| err.to_string())
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Filter::All => "all devices".to_string(),
+            Filter::Device => "this device".to_string(),
+            Filter::Warnings => "warnings and errors".to_string(),
+            Filter::Errors => "errors".to_string(),
+            Filter::Regex(regex) => format!("matching {}", regex.as_str()),
+        }
+    }
+
+    //
+    // The next of the fixed modes, a regex goes back to the first.
+    //
+    pub fn next(&self) -> Filter {
+        match self {
+            Filter::Device => Filter::All,
+            Filter::All => Filter::Warnings,
+            Filter::Warnings => Filter::Errors,
+            Filter::Errors | Filter::Regex(_) => Filter::Device,
+        }
+    }
+
+    //
+    // device is the name minerator starts the device's lines with.
+    //
+    pub fn matches(&self, line: &str, device: &str) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Device => line.contains(&format!("{}: ", device)) || line.contains("Fee"),
+            Filter::Warnings => matches!(level(line), Some("WRN") | Some("ERR")),
+            Filter::Errors => level(line) == Some("ERR"),
+            Filter::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
@@ -1,5 +1,9 @@
 use directories::ProjectDirs;
 use log::*;
+use mylib::config;
+use mylib::energy;
+use mylib::history;
+use mylib::scan;
 use mylib::screen;
 use mylib::webdata;
 use pancurses::endwin;
@@ -8,6 +12,7 @@ use std::fs;
 use std::fs::OpenOptions;
 use std::panic;
 use std::path::Path;
+use std::time::Duration;
 
 fn main() {
     let matches = clap::App::new(clap::crate_name!())
@@ -33,7 +38,7 @@ fn main() {
                 .short("h")
                 .long("host")
                 .value_name("url")
-                .help("Read JSON from different url rather than http://localhost"),
+                .help("Read JSON from different url rather than the config's first host or http://localhost"),
         )
         .arg(
             clap::Arg::with_name("input_file")
@@ -48,10 +53,67 @@ fn main() {
                 .long("debug")
                 .help("Runs in debug mode, which allows normal panics with trace"),
         )
+        .subcommand(
+            clap::SubCommand::with_name("scan")
+                .about("Probes a subnet or list of hosts for running minerators")
+                .arg(
+                    clap::Arg::with_name("targets")
+                        .value_name("SUBNET|HOST")
+                        .help("Subnets (10.0.0.0/24), hosts or urls to probe, comma separated or repeated")
+                        .required(true)
+                        .multiple(true),
+                )
+                .arg(
+                    clap::Arg::with_name("timeout")
+                        .short("t")
+                        .long("timeout")
+                        .value_name("SECONDS")
+                        .help("How long to wait for each host to answer (default 2)"),
+                )
+                .arg(
+                    clap::Arg::with_name("write")
+                        .short("w")
+                        .long("write")
+                        .help("Adds the hosts found to the fleet in the config file"),
+                ),
+        )
+        .subcommand(
+            clap::SubCommand::with_name("energy")
+                .about("Exports the daily kWh totals of each device and the rig of --host as CSV")
+                .arg(
+                    clap::Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Writes the CSV to a file rather than standard output"),
+                ),
+        )
         .get_matches();
-    init_logging(&matches);
+    let config_dir = init_logging(&matches);
+
+    if let Some(scan_matches) = matches.subcommand_matches("scan") {
+        run_scan(scan_matches, &config_dir);
+        return;
+    }
+    if let Some(energy_matches) = matches.subcommand_matches("energy") {
+        let host = watched_host(&matches, &config::Config::load(&config_dir));
+        run_energy(energy_matches, &config_dir, &host);
+        return;
+    }
 
-    let mut scr = screen::Screen::new(webdata::WebData::new());
+    let config = config::Config::load(&config_dir);
+    let mut wd = webdata::WebData::new();
+    wd.history = history::History::new(
+        Duration::from_secs(config.history_retention_secs),
+        config.history_capacity,
+    );
+    wd.host = watched_host(&matches, &config);
+    if !matches.is_present("input_file") {
+        let host = wd.host.clone();
+        wd.load_snapshot(&config_dir, &host);
+        wd.energy = Some(energy::Energy::load(&config_dir, &host));
+    }
+    let mut scr = screen::Screen::new(wd, config);
     if !matches.is_present("debug") {
         panic::set_hook(Box::new(|panic_info| {
             endwin();
@@ -75,7 +137,75 @@ fn main() {
     endwin();
 }
 
-fn init_logging<'a>(matches: &clap::ArgMatches<'a>) {
+fn run_scan<'a>(matches: &clap::ArgMatches<'a>, config_dir: &str) {
+    let targets: Vec<&str> = matches.values_of("targets").unwrap().collect();
+    let hosts = match scan::expand_targets(&targets) {
+        Ok(hosts) => hosts,
+        Err(err) => {
+            eprintln!("{}", err);
+            return;
+        }
+    };
+    // a config that didn't parse can't be saved back without losing all but the hosts
+    let mut config = config::Config::load(config_dir);
+    if matches.is_present("write") && !config.writable() {
+        eprintln!(
+            "Unable to parse {}, fix it before adding hosts to it",
+            config::Config::path(config_dir).display()
+        );
+        return;
+    }
+    let timeout = matches.value_of("timeout").and_then(|t| t.parse().ok()).unwrap_or(2);
+    println!("Probing {} hosts...", hosts.len());
+    let found = scan::scan(&hosts, Duration::from_secs(timeout), 64);
+    for result in &found {
+        println!(
+            "{:40} minerator {:12} {:>3} devices",
+            result.host, result.minerator, result.devices
+        );
+    }
+    println!("Found {} minerator hosts out of {} probed", found.len(), hosts.len());
+
+    if matches.is_present("write") && !found.is_empty() {
+        let found_hosts: Vec<String> = found.into_iter().map(|r| r.host).collect();
+        let added = config.add_hosts(&found_hosts);
+        match config.save_back() {
+            Ok(()) => println!(
+                "Added {} new hosts to {}",
+                added,
+                config::Config::path(config_dir).display()
+            ),
+            Err(err) => eprintln!("Unable to write config: {}", err),
+        }
+    }
+}
+
+//
+// The minerator to watch, from --host or else the first in the config's fleet.
+//
+fn watched_host<'a>(matches: &clap::ArgMatches<'a>, config: &config::Config) -> String {
+    match matches.value_of("host") {
+        Some(host) => host.to_string(),
+        None => config
+            .hosts
+            .first()
+            .cloned()
+            .unwrap_or_else(|| webdata::DEFAULT_HOST.to_string()),
+    }
+}
+
+fn run_energy<'a>(matches: &clap::ArgMatches<'a>, config_dir: &str, host: &str) {
+    let csv = energy::Energy::load(config_dir, host).to_csv();
+    match matches.value_of("output") {
+        Some(file) => match fs::write(file, csv) {
+            Ok(()) => println!("Wrote daily energy totals to {}", file),
+            Err(err) => eprintln!("Unable to write {}: {}", file, err),
+        },
+        None => print!("{}", csv),
+    }
+}
+
+fn init_logging<'a>(matches: &clap::ArgMatches<'a>) -> String {
     let appname = clap::crate_name!();
     let version = clap::crate_version!();
     let mut default_config = String::from("/tmp");
@@ -121,4 +251,5 @@ fn init_logging<'a>(matches: &clap::ArgMatches<'a>) {
     if created_dir {
         info!("Created new config directory: {}", config_dir);
     }
+    config_dir.to_string()
 }
@@ -0,0 +1,159 @@
+//
+// Figures derived from the raw minerator stats, shared by the screen and
+// anything else that wants them.
+//
+use crate::webdata;
+
+// minerator counts hashes in the stats in millions
+pub const HASHES_PER_STAT_UNIT: f64 = 1_000_000.0;
+
+//
+// Seconds the stats block covers, the times are in nanoseconds.
+//
+pub fn span_secs(stat: &webdata::StatDetail) -> f32 {
+    (stat.endTime - stat.startTime) / 1_000_000_000.0
+}
+
+//
+// Per second rate of val over the time span the stats block covers.  A block
+// that doesn't cover any time yet, or has its times backwards, has no rate.
+//
+pub fn total_rate(stat: &webdata::StatDetail, val: f32) -> f32 {
+    let div = span_secs(stat);
+    if div > 0.0 && div.is_finite() {
+        val / div
+    } else {
+        0.0
+    }
+}
+
+pub fn minute_rate(val: f32) -> f32 {
+    val / 60.0
+}
+
+//
+// Hashes per joule, which is just the hashrate divided by the watts going in.
+// None when there is no power reading to divide by.
+//
+pub fn efficiency(rate: f32, power: f32) -> Option<f32> {
+    if power > 0.0 && rate.is_finite() {
+        Some(rate / power)
+    } else {
+        None
+    }
+}
+
+//
+// part as a percentage of whole, None when there is no whole to speak of
+//
+pub fn percent(part: f32, whole: f32) -> Option<f32> {
+    if whole > 0.0 {
+        Some(part / whole * 100.0)
+    } else {
+        None
+    }
+}
+
+pub fn bad_nonce_percent(clock: &webdata::Clock) -> Option<f32> {
+    percent(clock.badNonces, clock.totalNonces)
+}
+
+pub fn valid_percent(stat: &webdata::StatDetail) -> Option<f32> {
+    percent(stat.valid, stat.found)
+}
+
+pub fn reject_percent(stat: &webdata::StatDetail) -> Option<f32> {
+    percent(stat.submitted - stat.accepted, stat.submitted)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Efficiency {
+    pub total: Option<f32>,
+    pub minute: Option<f32>,
+}
+
+pub fn fleet_power(workers: &[webdata::Worker]) -> f32 {
+    workers.iter().map(|w| w.input_power).sum()
+}
+
+pub fn device_efficiency(w: &webdata::Worker) -> Efficiency {
+    let (total, minute) = accepted_rates(std::slice::from_ref(w));
+    Efficiency {
+        total: efficiency(total, w.input_power),
+        minute: efficiency(minute, w.input_power),
+    }
+}
+
+pub fn fleet_efficiency(workers: &[webdata::Worker]) -> Efficiency {
+    let (total, minute) = accepted_rates(workers);
+    let power = fleet_power(workers);
+    Efficiency {
+        total: efficiency(total, power),
+        minute: efficiency(minute, power),
+    }
+}
+
+//
+// Accepted rate summed over every core of the workers, since start and over the
+// last minute.
+//
+pub fn accepted_rates(workers: &[webdata::Worker]) -> (f32, f32) {
+    let mut total = 0.0;
+    let mut minute = 0.0;
+    for core in workers.iter().flat_map(|w| w.cores.cores.iter()) {
+        total += total_rate(&core.stats.total, core.stats.total.accepted);
+        minute += minute_rate(core.stats.minute.accepted);
+    }
+    (total, minute)
+}
+
+//
+// Number of shares the pool accepted, worked back from the accepted hashes and
+// what each share is worth.  None if the difficulty isn't known.
+//
+pub fn accepted_shares(algo: &webdata::Algo, stat: &webdata::StatDetail) -> Option<f64> {
+    let per_share = algo.hashes_per_share();
+    if per_share > 0.0 {
+        Some((f64::from(stat.accepted) * HASHES_PER_STAT_UNIT / per_share).round())
+    } else {
+        None
+    }
+}
+
+//
+// Pool side effective hashrate, accepted shares times what each is worth over
+// the time they took, in the same units as the stats rates.
+//
+pub fn effective_rate(algo: &webdata::Algo, stat: &webdata::StatDetail, seconds: f32) -> Option<f32> {
+    let shares = accepted_shares(algo, stat)?;
+    if seconds > 0.0 {
+        Some((shares * algo.hashes_per_share() / HASHES_PER_STAT_UNIT) as f32 / seconds)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Effective {
+    pub total: Option<f32>,
+    pub minute: Option<f32>,
+}
+
+pub fn effective_hashrate(algo: &webdata::Algo) -> Effective {
+    let total = &algo.stats.total;
+    Effective {
+        total: effective_rate(algo, total, span_secs(total)),
+        minute: effective_rate(algo, &algo.stats.minute, 60.0),
+    }
+}
+
+//
+// How far the pool side rate is from what the device calculated it did, in
+// percent.  A card producing bad work shows up as a growing negative gap.
+//
+pub fn hashrate_gap(effective: Option<f32>, calculated: f32) -> Option<f32> {
+    match effective {
+        Some(effective) if calculated > 0.0 => Some((effective - calculated) / calculated * 100.0),
+        _ => None,
+    }
+}
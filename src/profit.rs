@@ -0,0 +1,108 @@
+use serde::{Deserialize, Serialize};
+
+use crate::metrics;
+
+const SECONDS_PER_DAY: f64 = 24.0 * 60.0 * 60.0;
+
+//
+// What a day of mining is worth, worked out offline from numbers kept in the
+// config.  The network difficulty is taken as hashes per block times
+// hashes_per_difficulty, so coins that quote difficulty differently only need
+// that multiplier changed.  Electricity is a flat rate per kWh unless a
+// time-of-use tariff covers the hour.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Profit {
+    pub coin_price: f64,
+    pub network_difficulty: f64,
+    pub hashes_per_difficulty: f64,
+    pub block_reward: f64,
+    pub electricity_rate: f64,
+    pub tariffs: Vec<Tariff>,
+}
+
+//
+// Rate per kWh from from_hour up to but not including to_hour, wrapping past
+// midnight when to_hour is the smaller.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Tariff {
+    pub from_hour: u32,
+    pub to_hour: u32,
+    pub rate: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Estimate {
+    pub revenue: f64,
+    pub cost: f64,
+    pub profit: f64,
+}
+
+impl Default for Profit {
+    fn default() -> Self {
+        Profit {
+            coin_price: 0.0,
+            network_difficulty: 0.0,
+            hashes_per_difficulty: 1.0,
+            block_reward: 0.0,
+            electricity_rate: 0.0,
+            tariffs: vec![],
+        }
+    }
+}
+
+impl Tariff {
+    pub fn covers(&self, hour: u32) -> bool {
+        if self.from_hour <= self.to_hour {
+            hour >= self.from_hour && hour < self.to_hour
+        } else {
+            hour >= self.from_hour || hour < self.to_hour
+        }
+    }
+}
+
+impl Profit {
+    //
+    // Electricity rate for an hour of the day, the first tariff covering it wins.
+    //
+    pub fn rate_at(&self, hour: u32) -> f64 {
+        self.tariffs
+            .iter()
+            .find(|t| t.covers(hour))
+            .map_or(self.electricity_rate, |t| t.rate)
+    }
+
+    pub fn revenue_per_day(&self, hashrate: f32) -> f64 {
+        let hashes_per_block = self.network_difficulty * self.hashes_per_difficulty;
+        if hashes_per_block <= 0.0 {
+            return 0.0;
+        }
+        let hashes = f64::from(hashrate) * metrics::HASHES_PER_STAT_UNIT * SECONDS_PER_DAY;
+        hashes / hashes_per_block * self.block_reward * self.coin_price
+    }
+
+    //
+    // Cost of drawing a steady number of watts for a day, hour by hour so the
+    // tariffs are weighed by how long they apply.
+    //
+    pub fn cost_per_day(&self, power: f32) -> f64 {
+        let kw = f64::from(power) / 1000.0;
+        (0..24).map(|hour| kw * self.rate_at(hour)).sum()
+    }
+
+    //
+    // Daily figures for an accepted hashrate, in stats units per second, and the
+    // watts it takes.
+    //
+    pub fn estimate(&self, hashrate: f32, power: f32) -> Estimate {
+        let revenue = self.revenue_per_day(hashrate);
+        let cost = self.cost_per_day(power);
+        Estimate {
+            revenue,
+            cost,
+            profit: revenue - cost,
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use crate::config;
+use crate::metrics;
+use crate::webdata;
+
+//
+// One comparison of the vccint regulator against what it should be doing,
+// with the health level its tolerance puts it at.
+//
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reading {
+    pub label: &'static str,
+    pub value: f32,
+    pub unit: &'static str,
+    pub health: &'static str,
+}
+
+//
+// Percent the measured voltage sits below the setpoint, negative when above.
+//
+pub fn droop(measured: f32, setpoint: f32) -> Option<f32> {
+    metrics::percent(setpoint - measured, setpoint)
+}
+
+//
+// Percent two voltages differ by, relative to their mean.
+//
+pub fn imbalance(a: f32, b: f32) -> Option<f32> {
+    metrics::percent((a - b).abs(), (a + b) / 2.0)
+}
+
+//
+// Droop of the ADC reading and of the regulator's own output against the
+// setpoint, and on BCUs the balance of output voltage and temperature between
+// the two phases.  A failing phase shows up as asymmetry well before
+// minerator's vrPower health changes.
+//
+pub fn readings(w: &webdata::Worker, tolerance: &config::Regulator) -> Vec<Reading> {
+    let mut readings = vec![];
+    let mut add = |label, value: Option<f32>, unit, threshold: &config::Threshold| {
+        if let Some(value) = value {
+            readings.push(Reading {
+                label,
+                value,
+                unit,
+                health: threshold.health(value.abs()),
+            });
+        }
+    };
+    if let Some(setpoint) = w.vccint_setpoint {
+        add("vccint droop", droop(w.vccint, setpoint), "%", &tolerance.droop_percent);
+        if w.voltage > 0.0 {
+            add("vout droop", droop(w.voltage, setpoint), "%", &tolerance.droop_percent);
+        }
+    }
+    if w.hw_type == webdata::HWTYPE_BCU {
+        add(
+            "vout imbalance",
+            imbalance(w.phase0_vout, w.phase1_vout),
+            "%",
+            &tolerance.vout_imbalance_percent,
+        );
+        add(
+            "temp imbalance",
+            Some((w.phase0_temperature - w.phase1_temperature).abs()),
+            "C",
+            &tolerance.temperature_imbalance,
+        );
+    }
+    readings
+}
+
+//
+// The reading furthest out of tolerance, if any is.
+//
+pub fn worst(readings: &[Reading]) -> Option<&Reading> {
+    ["critical", "hold"]
+        .iter()
+        .find_map(|level| readings.iter().find(|r| r.health == *level))
+}
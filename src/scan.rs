@@ -0,0 +1,129 @@
+use log::*;
+use std::collections::HashSet;
+use std::net::Ipv4Addr;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::webdata;
+
+// Refuse to expand anything larger than a /16 so a typo doesn't start a scan of
+// half the internet.
+const MAX_TARGETS: usize = 65536;
+
+pub struct ScanResult {
+    pub host: String,
+    pub minerator: String,
+    pub devices: usize,
+}
+
+//
+// Turns the scan arguments into a list of host urls.  Each argument may be a
+// CIDR subnet (10.0.0.0/24), a single host or url, or a comma separated list of
+// either.
+//
+pub fn expand_targets(targets: &[&str]) -> Result<Vec<String>, String> {
+    let mut hosts = Vec::new();
+    for target in targets.iter().flat_map(|t| t.split(',')) {
+        let target = target.trim();
+        if target.is_empty() {
+            continue;
+        }
+        if target.contains('/') && !target.contains("://") {
+            for addr in expand_cidr(target)? {
+                hosts.push(host_url(&addr.to_string()));
+            }
+        } else {
+            hosts.push(host_url(target));
+        }
+        if hosts.len() > MAX_TARGETS {
+            return Err(format!("Too many hosts to scan, limit is {}", MAX_TARGETS));
+        }
+    }
+    // overlapping targets name some hosts more than once, the first is kept
+    let mut seen = HashSet::new();
+    hosts.retain(|host| seen.insert(host.clone()));
+    Ok(hosts)
+}
+
+//
+// Lists the usable addresses in a subnet, skipping the network and broadcast
+// addresses unless the subnet is too small to have them.
+//
+pub fn expand_cidr(cidr: &str) -> Result<Vec<Ipv4Addr>, String> {
+    let mut parts = cidr.splitn(2, '/');
+    let addr: Ipv4Addr = parts
+        .next()
+        .unwrap_or("")
+        .parse()
+        .map_err(|_| format!("Invalid address in '{}'", cidr))?;
+    let prefix: u32 = parts
+        .next()
+        .unwrap_or("")
+        .parse()
+        .map_err(|_| format!("Invalid prefix length in '{}'", cidr))?;
+    if prefix > 32 {
+        return Err(format!("Invalid prefix length in '{}'", cidr));
+    }
+    let size = 1u64 << (32 - prefix);
+    if size as usize > MAX_TARGETS {
+        return Err(format!("Subnet '{}' is too large, use /16 or smaller", cidr));
+    }
+    let mask = if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) };
+    let network = u64::from(u32::from(addr) & mask);
+    let (first, last) = if size > 2 {
+        (network + 1, network + size - 2)
+    } else {
+        (network, network + size - 1)
+    };
+    Ok((first..=last).map(|a| Ipv4Addr::from(a as u32)).collect())
+}
+
+pub fn host_url(host: &str) -> String {
+    if host.contains("://") {
+        host.trim_end_matches('/').to_string()
+    } else {
+        format!("http://{}", host)
+    }
+}
+
+//
+// Probes every host for a responding /api/status, using up to `threads` probes
+// at a time.  Results come back in the same order as the hosts were given.
+//
+pub fn scan(hosts: &[String], timeout: Duration, threads: usize) -> Vec<ScanResult> {
+    let queue = Arc::new(Mutex::new(hosts.iter().cloned().enumerate().collect::<Vec<_>>()));
+    let (tx, rx) = mpsc::channel();
+    let mut handles = Vec::new();
+    for _ in 0..threads.max(1).min(hosts.len()) {
+        let queue = Arc::clone(&queue);
+        let tx = tx.clone();
+        handles.push(thread::spawn(move || {
+            let wd = webdata::WebData::with_timeout(timeout);
+            loop {
+                let next = queue.lock().unwrap().pop();
+                let (index, host) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+                if let Some((minerator, devices)) = wd.probe(&host) {
+                    info!("Found minerator {} with {} devices at {}", minerator, devices, host);
+                    let result = ScanResult {
+                        host,
+                        minerator,
+                        devices,
+                    };
+                    tx.send((index, result)).unwrap();
+                }
+            }
+        }));
+    }
+    drop(tx);
+    let mut results: Vec<(usize, ScanResult)> = rx.iter().collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
@@ -1,253 +1,1703 @@
 use log::*;
-use pancurses::{cbreak, endwin, init_pair, initscr, noecho, start_color, Input, Window, ACS_HLINE};
+use pancurses::{cbreak, init_pair, initscr, noecho, start_color, Input, Window, ACS_HLINE};
+use std::cell::RefCell;
 use std::convert::TryInto;
-use std::process::{exit, ExitCode, Termination};
+use std::env;
+use std::time::{Duration, SystemTime};
 
+use crate::chart;
+use crate::config;
+use crate::energy;
+use crate::fleet;
+use crate::health;
+use crate::history::{self, Metric};
+use crate::keymap;
+use crate::layout::{self, Panel};
 use crate::log_display;
+use crate::metrics;
+use crate::regulator;
+use crate::sparkline;
+use crate::theme;
+use crate::units;
 use crate::webdata;
 
 pub struct Screen {
     window: Window,
     wd: webdata::WebData,
+    config: config::Config,
     x: i32,
     y: i32,
     current_worker: usize,
+    show_sparklines: bool,
+    show_session: bool,
+    view: View,
+    chart_metric: Metric,
+    chart_window: usize,
+    chart_overlay: bool,
+    chart_fleet: bool,
+    stats_mode: StatsMode,
+    event_scroll: usize,
+    fleet_sort: fleet::Column,
+    fleet_descending: bool,
+    keymap: keymap::Keymap,
+    paused: bool,
+    show_help: bool,
+    help_scroll: usize,
+    monochrome: bool,
+    gradient_steps: usize,
+    log: log_display::LogView,
+    log_filter: log_display::Filter,
+    // what a click or the wheel lands on, from the last time the screen was drawn
+    targets: RefCell<Vec<(layout::Rect, Target)>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Target {
+    Device(usize),
+    StepDevice(i32),
+    SortColumn(fleet::Column),
+    Log,
+    Events,
+}
+
+#[derive(PartialEq)]
+enum View {
+    Device,
+    Chart,
+    Health,
+    Fleet,
+}
+
+//
+// One row of a zoomed sysmons or phases panel, a heading when it has no value.
+// Temperatures carry their health for coloring, and readings the metric whose
+// history is drawn across the rest of the row.
+//
+struct Detail<'a> {
+    label: String,
+    value: String,
+    temperature: Option<(f32, &'a str)>,
+    metric: Option<Metric>,
+}
+
+impl<'a> Detail<'a> {
+    fn heading(label: String) -> Detail<'a> {
+        Detail {
+            label,
+            value: String::new(),
+            temperature: None,
+            metric: None,
+        }
+    }
+
+    fn reading(label: &str, value: f32, metric: Metric) -> Detail<'a> {
+        Detail {
+            label: format!("  {}", label),
+            value: Screen::float_to_string3(value),
+            temperature: None,
+            metric: Some(metric),
+        }
+    }
+
+    fn temperature(value: f32, health: &'a str, metric: Metric) -> Detail<'a> {
+        Detail {
+            temperature: Some((value, health)),
+            ..Detail::reading("temperature", value, metric)
+        }
+    }
+}
+
+#[derive(PartialEq)]
+enum StatsMode {
+    Rate,
+    Efficiency,
+    Count,
 }
 
-const MIN_X: i32 = 126;
-const MIN_Y: i32 = 26;
 const HEALTH_COLOR_RAMPUP: i16 = 1;
 const HEALTH_COLOR_SLOWINCREASE: i16 = 2;
 const HEALTH_COLOR_HOLD: i16 = 3;
 const HEALTH_COLOR_SLOWDECREASE: i16 = 4;
 const HEALTH_COLOR_CRITICAL: i16 = 5;
+const CHART_COLOR_BASE: i16 = 10;
+const CHART_COLORS: [i16; 6] = [
+    pancurses::COLOR_GREEN,
+    pancurses::COLOR_CYAN,
+    pancurses::COLOR_YELLOW,
+    pancurses::COLOR_MAGENTA,
+    pancurses::COLOR_RED,
+    pancurses::COLOR_BLUE,
+];
+const GRADIENT_COLOR_BASE: i16 = 20;
+const GRADIENT_TRUECOLOR_STEPS: usize = 32;
+// colors redefined for the truecolor gradient, out of the way of the 16 basic ones
+const GRADIENT_TRUECOLOR_FIRST: i16 = 200;
+// the longest is as far back as the history keeps its minute averages
+const CHART_WINDOWS: [(&str, u64); 3] = [
+    ("5m", 5 * 60),
+    ("1h", 60 * 60),
+    ("24h", history::DOWNSAMPLED_RETENTION_SECS),
+];
+const CHART_AXIS_WIDTH: i32 = 9;
+const SPARKLINE_WIDTH: usize = 8;
+// where the history starts on the rows of a zoomed panel
+const DETAIL_TREND_X: i32 = 28;
+// a session min and max of five characters each
+const SESSION_WIDTH: usize = 11;
+const REGULATOR_PANEL_WIDTH: i32 = 24;
+// lines one notch of the mouse wheel scrolls
+const WHEEL_LINES: usize = 3;
+// devices page up and page down move by, as many as the number keys reach
+const DEVICE_PAGE: i32 = 9;
+// percent the pool side effective hashrate may fall short of the calculated one
+const EFFECTIVE_GAP_WARN: f32 = 5.0;
+const EFFECTIVE_GAP_CRITICAL: f32 = 10.0;
 
 impl Screen {
     //
     // Basically just creates a screen object (ala window in curses)
     //
-    pub fn new(wd: webdata::WebData) -> Screen {
+    pub fn new(wd: webdata::WebData, config: config::Config) -> Screen {
         Screen {
             window: initscr(),
             x: 0,
             y: 0,
             current_worker: 0,
+            show_sparklines: true,
+            show_session: false,
+            view: View::Device,
+            chart_metric: Metric::Hashrate,
+            chart_window: 0,
+            chart_overlay: false,
+            chart_fleet: false,
+            stats_mode: StatsMode::Rate,
+            event_scroll: 0,
+            fleet_sort: fleet::Column::Index,
+            fleet_descending: false,
+            keymap: keymap::Keymap::new(&config.keys),
+            paused: false,
+            show_help: false,
+            help_scroll: 0,
+            monochrome: false,
+            gradient_steps: 0,
+            log: log_display::LogView::new(),
+            log_filter: log_display::Filter::default(),
+            targets: RefCell::new(vec![]),
             wd,
+            config,
         }
     }
 
     //
-    // Create initial screen and ensure we have a large enough window to support
-    // what we want to accomplish.
+    // Create initial screen.  Terminals too small for the full layout get the
+    // compact one.
     //
     pub fn init(&mut self) {
         self.x = self.window.get_max_x();
         self.y = self.window.get_max_y();
-        if self.x < MIN_X {
-            endwin();
-            println!(
-                "Console screen must be at least {} columns in X, current X is {}",
-                MIN_X, self.x
-            );
-            error!(
-                "Console screen must be at least {} columns in X, current X is {}",
-                MIN_X, self.x
-            );
-            exit(ExitCode::SUCCESS.report())
+        debug!("Screen is {} X x {} Y", self.x, self.y);
+
+        let no_color = env::var("NO_COLOR").ok();
+        self.monochrome = theme::monochrome(&self.config.theme, no_color.as_deref()) || !pancurses::has_colors();
+        if self.monochrome {
+            info!("No colors, health shown with attributes and markers");
+        } else {
+            self.init_colors();
+        }
+
+        if self.config.mouse {
+            pancurses::mousemask(pancurses::ALL_MOUSE_EVENTS, std::ptr::null_mut());
+        }
+
+        pancurses::set_title(&*format!("Viewerator v{}", clap::crate_version!()));
+        self.window.keypad(true);
+        self.window.timeout(1000);
+        cbreak();
+        noecho();
+
+        self.window.refresh();
+    }
+
+    //
+    // Color pairs for the health levels from the theme, the chart lines, and
+    // the temperature gradient if the terminal has enough colors for one.
+    //
+    fn init_colors(&mut self) {
+        start_color();
+        let colors = pancurses::COLORS();
+        let pairs = [
+            HEALTH_COLOR_CRITICAL,
+            HEALTH_COLOR_SLOWDECREASE,
+            HEALTH_COLOR_HOLD,
+            HEALTH_COLOR_SLOWINCREASE,
+            HEALTH_COLOR_RAMPUP,
+        ];
+        // resolve follows LEVELS, worst first
+        for (pair, (fg, bg)) in pairs.iter().zip(theme::resolve(&self.config.theme, colors)) {
+            init_pair(*pair, fg, bg);
+        }
+        for (i, color) in CHART_COLORS.iter().enumerate() {
+            init_pair(CHART_COLOR_BASE + i as i16, *color, pancurses::COLOR_BLACK);
+        }
+
+        if !self.config.theme.gradient || colors < 256 {
+            return;
+        }
+        let truecolor = matches!(env::var("COLORTERM").as_deref(), Ok("truecolor") | Ok("24bit"));
+        if truecolor && pancurses::can_change_color() {
+            for i in 0..GRADIENT_TRUECOLOR_STEPS {
+                let (r, g, b) = theme::gradient_rgb(i as f32 / (GRADIENT_TRUECOLOR_STEPS - 1) as f32);
+                let color = GRADIENT_TRUECOLOR_FIRST + i as i16;
+                pancurses::init_color(color, r, g, b);
+                init_pair(GRADIENT_COLOR_BASE + i as i16, color, pancurses::COLOR_BLACK);
+            }
+            self.gradient_steps = GRADIENT_TRUECOLOR_STEPS;
+        } else {
+            for (i, color) in theme::GRADIENT_256.iter().enumerate() {
+                init_pair(GRADIENT_COLOR_BASE + i as i16, *color, pancurses::COLOR_BLACK);
+            }
+            self.gradient_steps = theme::GRADIENT_256.len();
+        }
+        info!("Temperature gradient of {} shades", self.gradient_steps);
+    }
+
+    pub fn mainloop<'a>(&mut self, matches: &clap::ArgMatches<'a>) {
+        let mut ld = log_display::LogDisplay::new();
+        if !self.wd.workers.is_empty() {
+            // a snapshot from last time, show it while we wait for minerator
+            self.draw_screen(matches, &mut ld);
+            self.window.refresh();
+        }
+        loop {
+            let input = self.window.getch();
+            if self.show_help && input.is_some() && input != Some(Input::KeyResize) {
+                // the arrows and page keys scroll the help, any other closes it
+                // without doing what it usually does
+                let page = layout::help_box(self.x, self.y).map_or(1, |r| (r.height - 2).max(1) as usize);
+                match input {
+                    Some(Input::KeyUp) => self.help_scroll = self.help_scroll.saturating_sub(1),
+                    Some(Input::KeyDown) => self.help_scroll += 1,
+                    Some(Input::KeyPPage) => self.help_scroll = self.help_scroll.saturating_sub(page),
+                    Some(Input::KeyNPage) => self.help_scroll += page,
+                    _ => {
+                        self.show_help = false;
+                        self.help_scroll = 0;
+                    }
+                }
+                self.window.erase();
+                self.draw_screen(matches, &mut ld);
+                self.window.refresh();
+                continue;
+            }
+            match input {
+                Some(Input::Character(c)) if c.is_ascii_digit() => {
+                    // 1-9 pick the first nine devices, search reaches the rest
+                    let w: usize = c.to_digit(10).unwrap().try_into().unwrap();
+                    if w >= 1 && w <= self.wd.workers.len() {
+                        self.select_device(w - 1);
+                        self.update_screen(matches, &mut ld);
+                    }
+                }
+                Some(Input::KeyMouse) => {
+                    if let Ok(event) = pancurses::getmouse() {
+                        if self.mouse(event) {
+                            self.draw_screen(matches, &mut ld);
+                        }
+                    }
+                }
+                Some(Input::KeyResize) => {
+                    pancurses::resize_term(0, 0);
+                    self.x = self.window.get_max_x();
+                    self.y = self.window.get_max_y();
+                    debug!("Screen resized to {} X x {} Y", self.x, self.y);
+                    self.window.erase();
+                    self.draw_screen(matches, &mut ld);
+                }
+                Some(input) => match self.keymap.action(&input) {
+                    Some(keymap::Action::Quit) => break,
+                    Some(keymap::Action::LogSearch) => self.search_log(matches, &mut ld),
+                    Some(action) => {
+                        self.perform(action);
+                        self.draw_screen(matches, &mut ld);
+                    }
+                    None => {
+                        if let Input::Character(c) = input {
+                            if (self.view == View::Chart && self.chart_key(c))
+                                || (self.view == View::Fleet && self.fleet_key(c))
+                            {
+                                self.draw_screen(matches, &mut ld);
+                            }
+                        }
+                    }
+                },
+                None if self.paused => {}
+                None => {
+                    self.update_screen(matches, &mut ld);
+                }
+            }
+            self.window.refresh();
+        }
+        self.wd.save_snapshot();
+        if let Some(energy) = &mut self.wd.energy {
+            if let Err(err) = energy.save() {
+                warn!("Unable to save energy totals: {}", err);
+            }
+        }
+        info!("Exiting..");
+    }
+
+    //
+    // Clicks select devices and sort the fleet table, the wheel scrolls the
+    // log and event panes.  True if the screen needs drawing again.
+    //
+    fn mouse(&mut self, event: pancurses::MEVENT) -> bool {
+        let target = self
+            .targets
+            .borrow()
+            .iter()
+            .rev()
+            .find(|(rect, _)| rect.contains(event.y, event.x))
+            .map(|(_, target)| *target);
+        let target = match target {
+            Some(target) => target,
+            None => return false,
+        };
+        debug!("Mouse {:#x} at {},{} on {:?}", event.bstate, event.x, event.y, target);
+        let click = event.bstate & (pancurses::BUTTON1_CLICKED | pancurses::BUTTON1_PRESSED) != 0;
+        let wheel_up = event.bstate & pancurses::BUTTON4_PRESSED != 0;
+        let wheel_down = event.bstate & pancurses::BUTTON5_PRESSED != 0;
+        match target {
+            Target::Device(i) if click => self.select_device(i),
+            Target::StepDevice(step) if click => self.step_device(step),
+            Target::SortColumn(column) if click => {
+                if column == self.fleet_sort {
+                    self.fleet_descending = !self.fleet_descending;
+                } else {
+                    self.fleet_sort = column;
+                    self.fleet_descending = false;
+                }
+            }
+            Target::Log if wheel_up => self.log.scroll_by(WHEEL_LINES as i64),
+            Target::Log if wheel_down => self.log.scroll_by(-(WHEEL_LINES as i64)),
+            // scrolling up goes back to older lines
+            Target::Events if wheel_up => self.event_scroll += WHEEL_LINES,
+            Target::Events if wheel_down => self.event_scroll = self.event_scroll.saturating_sub(WHEEL_LINES),
+            _ => return false,
+        }
+        true
+    }
+
+    fn toggle_panel(&mut self, panel: Panel) {
+        let shown = self.config.layout.toggle(panel);
+        info!("{} panel {}", panel.name(), if shown { "on" } else { "off" });
+        self.save_layout();
+    }
+
+    //
+    // Layout changes are kept in the config so the next run starts the same.
+    //
+    fn save_layout(&mut self) {
+        self.window.erase();
+        if let Err(err) = self.config.save_back() {
+            warn!("Unable to save layout: {}", err);
+        }
+    }
+
+    fn target(&self, rect: layout::Rect, target: Target) {
+        self.targets.borrow_mut().push((rect, target));
+    }
+
+    //
+    // Does what a key bound in the keymap asks for, other than quitting.
+    //
+    fn perform(&mut self, action: keymap::Action) {
+        debug!("Key action {}", action.name());
+        match action {
+            keymap::Action::Quit | keymap::Action::LogSearch => {}
+            keymap::Action::NextMatch => {
+                self.log.next(true);
+            }
+            keymap::Action::PreviousMatch => {
+                self.log.next(false);
+            }
+            keymap::Action::LogFilter => {
+                self.set_log_filter(self.log_filter.next());
+            }
+            keymap::Action::LogRegex => {
+                let mut label = "Show log lines matching: ".to_string();
+                while let Some(pattern) = self.prompt(&label) {
+                    if pattern.is_empty() {
+                        break;
+                    }
+                    match log_display::Filter::regex(&pattern) {
+                        Ok(filter) => {
+                            self.set_log_filter(filter);
+                            break;
+                        }
+                        Err(err) => {
+                            let err = err.lines().last().unwrap_or_default().to_string();
+                            label = format!("{}, try again: ", err);
+                        }
+                    }
+                }
+            }
+            keymap::Action::Follow => {
+                self.log.toggle_follow();
+                info!("Log {}", if self.log.follow { "following" } else { "paused" });
+            }
+            keymap::Action::NextDevice => self.step_device(1),
+            keymap::Action::PreviousDevice => self.step_device(-1),
+            keymap::Action::LogPageNewer => self.log.page(false),
+            keymap::Action::LogPageOlder => self.log.page(true),
+            // the device view's pages are the log's, the other views page devices
+            keymap::Action::NextPage if self.pages_log() => self.log.page(false),
+            keymap::Action::PreviousPage if self.pages_log() => self.log.page(true),
+            keymap::Action::NextPage => self.step_device(DEVICE_PAGE),
+            keymap::Action::PreviousPage => self.step_device(-DEVICE_PAGE),
+            keymap::Action::Search => {
+                if let Some(answer) = self.prompt("Find device (number, name or hwUID): ") {
+                    match fleet::find(&self.wd.workers, &answer) {
+                        Some(i) => self.select_device(i),
+                        None => info!("No device matches {}", answer),
+                    }
+                }
+            }
+            keymap::Action::TogglePane => self.toggle_panel(Panel::Events),
+            keymap::Action::TogglePanel => {
+                let names: Vec<&str> = layout::PANELS.iter().map(|p| p.name()).collect();
+                if let Some(answer) = self.prompt(&format!("Show or hide ({}): ", names.join(", "))) {
+                    match Panel::parse(&answer) {
+                        Some(panel) => self.toggle_panel(panel),
+                        None => info!("No panel called {}", answer),
+                    }
+                }
+            }
+            keymap::Action::Zoom => {
+                let bcu =
+                    matches!(self.wd.workers.get(self.current_worker), Some(w) if w.hw_type == webdata::HWTYPE_BCU);
+                match self.config.layout.next_zoom(bcu) {
+                    Some(panel) => info!("Zoomed {}", panel.name()),
+                    None => info!("Zoom off"),
+                }
+                self.save_layout();
+            }
+            keymap::Action::Pause => {
+                self.paused = !self.paused;
+                info!("Polling {}", if self.paused { "paused" } else { "resumed" });
+            }
+            keymap::Action::Help => self.show_help = true,
+            keymap::Action::Sparklines => {
+                // the session columns take the sparklines' place, so this brings them back
+                if self.show_session {
+                    self.show_session = false;
+                    self.show_sparklines = true;
+                } else {
+                    self.show_sparklines = !self.show_sparklines;
+                }
+                info!("Sparklines {}", if self.show_sparklines { "on" } else { "off" });
+                self.window.erase();
+            }
+            keymap::Action::StatsMode => {
+                self.stats_mode = match self.stats_mode {
+                    StatsMode::Rate => StatsMode::Efficiency,
+                    StatsMode::Efficiency => StatsMode::Count,
+                    StatsMode::Count => StatsMode::Rate,
+                };
+            }
+            keymap::Action::Chart => self.toggle_view(View::Chart),
+            keymap::Action::Health => self.toggle_view(View::Health),
+            keymap::Action::Session => {
+                self.show_session = !self.show_session;
+                info!("Session columns {}", if self.show_session { "on" } else { "off" });
+                self.window.erase();
+            }
+            keymap::Action::Fleet => self.toggle_view(View::Fleet),
+            keymap::Action::ResetSession => {
+                info!("Resetting session statistics");
+                self.wd.session.reset();
+            }
+            keymap::Action::OlderEvents => self.event_scroll += 1,
+            keymap::Action::NewerEvents => self.event_scroll = self.event_scroll.saturating_sub(1),
+        }
+    }
+
+    //
+    // Switches to a full screen view, or back to the device view if it is
+    // already showing.
+    //
+    fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view { View::Device } else { view };
+        self.window.erase();
+    }
+
+    pub fn update_screen<'a>(&mut self, matches: &clap::ArgMatches<'a>, ld: &mut log_display::LogDisplay) {
+        debug!("Getting data");
+        self.wd.getdata(matches);
+        // a device that went away takes the one shown with it when it was last
+        let count = self.wd.workers.len();
+        if count > 0 && self.current_worker >= count {
+            self.current_worker = count - 1;
+            self.log.reset();
+            self.window.erase();
+        }
+        self.draw_screen(matches, ld);
+    }
+
+    pub fn draw_screen<'a>(&mut self, matches: &clap::ArgMatches<'a>, ld: &mut log_display::LogDisplay) {
+        let loginfo = ld.read_raw();
+        debug!("Updating screen");
+        self.targets.borrow_mut().clear();
+        self.draw_header();
+        match self.view {
+            _ if self.wd.workers.get(self.current_worker).is_none() => self.draw_waiting(2),
+            View::Device => self.draw_device(matches, &loginfo),
+            View::Chart => self.draw_chart(2),
+            View::Health => self.draw_health(2),
+            View::Fleet => self.draw_fleet(2),
+        }
+        self.draw_footer();
+        if self.show_help {
+            self.draw_help(matches);
+        }
+        self.window.mv(self.y - 1, self.x - 1);
+    }
+
+    //
+    // Stands in for the view until minerator reports a device to show.
+    //
+    fn draw_waiting(&self, y: i32) {
+        for row in y..self.y - 1 {
+            self.window.mv(row, 0);
+            self.window.clrtoeol();
+        }
+        self.window
+            .mvprintw(y + 2, 0, format!("Waiting for data from {}", self.wd.host));
+    }
+
+    fn draw_device<'a>(&mut self, matches: &clap::ArgMatches<'a>, loginfo: &[String]) {
+        let layout = self.layout();
+        for (i, w) in self.wd.workers.iter().enumerate() {
+            if i == self.current_worker {
+                self.window.mvprintw(2, 0, format!("HWUID: {}", w.dna));
+                self.window.mvprintw(3, 0, format!("Name:  {}", w.name));
+                let prefix = self.hash_prefix(w);
+                if let Some(r) = layout.get(Panel::Sensors) {
+                    self.draw_sensors(r.y, r.x, w, prefix);
+                }
+                if let Some(r) = layout.get(Panel::Summary) {
+                    self.draw_fleet_efficiency(r.y, r.x, prefix);
+                    self.draw_profit(r.y + 1, r.x, w);
+                }
+                if let Some(r) = layout.get(Panel::Energy) {
+                    self.draw_energy(r.y, r.x, r.width, w);
+                }
+                if let Some(r) = layout.get(Panel::Phases) {
+                    if layout.zoomed == Some(Panel::Phases) {
+                        self.draw_detail(r, &w.hw_uid, &Screen::phase_details(w));
+                    } else {
+                        self.draw_phases(r.y, r.x, w);
+                    }
+                }
+                if let Some(r) = layout.get(Panel::Sysmons) {
+                    if layout.zoomed == Some(Panel::Sysmons) {
+                        self.draw_detail(r, &w.hw_uid, &Screen::sysmon_details(&w.sysmons));
+                    } else {
+                        self.draw_sysmons(r.y, r.x, &w.hw_uid, &w.sysmons);
+                    }
+                }
+                for (_num, core) in w.cores.cores.iter().enumerate() {
+                    if let Some(r) = layout.get(Panel::Clock) {
+                        self.draw_clock(r.y, r.x, &w.hw_uid, &core.clock);
+                    }
+                    if let Some(r) = layout.get(Panel::Stats) {
+                        let compact = r.width < layout::STATS_WIDE_WIDTH;
+                        self.draw_stats(r.y, r.x, w, core, compact);
+                        if layout.zoomed == Some(Panel::Stats) {
+                            self.draw_device_stats(r, prefix, compact);
+                        }
+                    }
+                }
+                let readings = regulator::readings(w, &self.config.regulator);
+                if self.x >= 50 {
+                    self.draw_regulator_flag(3, 24, &readings);
+                }
+                if let Some(r) = layout.get(Panel::Regulator) {
+                    self.draw_regulator(r.y, r.x, &readings);
+                }
+            }
         }
-        if self.y < MIN_Y {
-            endwin();
-            println!(
-                "Console screen must be at least {} rows in Y, current Y is {}",
-                MIN_Y, self.y
+        if let Some(r) = layout.get(Panel::Events) {
+            self.draw_events(r);
+        }
+        if let (Some(r), false) = (layout.get(Panel::Log), matches.is_present("input_file")) {
+            let name = match self
+                .wd
+                .workers
+                .get(self.current_worker)
+                .and_then(|w| w.cores.cores.first())
+            {
+                Some(core) => core.stats.name.as_str(),
+                None => "",
+            };
+            let filtered: Vec<String> = loginfo
+                .iter()
+                .filter(|s| self.log_filter.matches(s, name))
+                .cloned()
+                .collect();
+            self.log.set_lines(filtered);
+            self.draw_log(r);
+        }
+    }
+
+    //
+    // The minerator log lines for the current board under a title saying
+    // whether it's following, how far back it is, and what the search found.
+    //
+    fn draw_log(&mut self, r: layout::Rect) {
+        self.target(r, Target::Log);
+        let width = r.width.max(0) as usize;
+        let shown = self.log.window((r.height - 1).max(0) as usize);
+        let mut title = format!("Log of {}, ", self.log_filter.label());
+        if self.log.follow {
+            title.push_str("following");
+        } else {
+            title.push_str(&format!(
+                "paused {} lines back, {} follows",
+                self.log.scroll,
+                self.keymap.describe(keymap::Action::Follow)
+            ));
+        }
+        if !self.log.query.is_empty() {
+            let position = match self.log.current {
+                Some(current) => format!(", at line {}", current + 1),
+                None => String::new(),
+            };
+            title.push_str(&format!(
+                "   /{} {} matches{}",
+                self.log.query,
+                self.log.match_count(),
+                position
+            ));
+        }
+        self.window.mvprintw(r.y, r.x, format!("{:w$.w$}", title, w = width));
+        for (row, i) in shown.enumerate() {
+            let y = r.y + 1 + row as i32;
+            let line = &self.log.lines()[i];
+            let level = match log_display::level(line) {
+                Some("ERR") => "critical",
+                Some("WRN") => "hold",
+                _ => "",
+            };
+            let attr = self.set_text_colors(level);
+            self.window.mvprintw(y, r.x, format!("{:w$.w$}", line, w = width));
+            self.window.attroff(attr);
+            let mut highlight = pancurses::A_REVERSE;
+            if self.log.current == Some(i) {
+                highlight |= pancurses::A_BOLD;
+            }
+            for range in log_display::highlights(line, &self.log.query) {
+                let column = line[..range.start].chars().count();
+                if column >= width {
+                    break;
+                }
+                self.window.attron(highlight);
+                self.window.mvprintw(
+                    y,
+                    r.x + column as i32,
+                    format!("{:.w$}", &line[range], w = width - column),
+                );
+                self.window.attroff(highlight);
+            }
+        }
+    }
+
+    fn set_log_filter(&mut self, filter: log_display::Filter) {
+        info!("Log showing {}", filter.label());
+        self.log_filter = filter;
+        self.log.reset();
+    }
+
+    //
+    // Reads the search from the bottom row, jumping to the newest match as it
+    // is typed.  Escape puts back the search and position from before.
+    //
+    fn search_log<'a>(&mut self, matches: &clap::ArgMatches<'a>, ld: &mut log_display::LogDisplay) {
+        let before = self.log.clone();
+        let mut query = String::new();
+        loop {
+            self.log.search(&query);
+            self.draw_screen(matches, ld);
+            self.window.mv(self.y - 1, 0);
+            self.window.clrtoeol();
+            self.window.printw(format!("/{}", query));
+            self.window.refresh();
+            match self.window.getch() {
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => break,
+                Some(Input::Character('\u{1b}')) => {
+                    self.log = before;
+                    break;
+                }
+                Some(Input::KeyBackspace) | Some(Input::Character('\u{7f}')) | Some(Input::Character('\u{8}')) => {
+                    query.pop();
+                }
+                Some(Input::Character(c)) if !c.is_control() => query.push(c),
+                _ => {}
+            }
+        }
+        info!("Log search for {}", self.log.query);
+    }
+
+    //
+    // Title, health legend, staleness and minerator version on the top row and
+    // the device selector under it.  Narrow terminals lose the legend and get a
+    // shorter title.
+    //
+    fn draw_header(&self) {
+        self.window.mv(0, 0);
+        self.window.clrtoeol();
+        let wide = self.x >= layout::WIDE_WIDTH;
+        if wide {
+            self.window.printw(format!(
+                "Viewerator v{}, press {} to exit    ",
+                clap::crate_version!(),
+                self.keymap.describe(keymap::Action::Quit)
+            ));
+            self.window.mv(0, self.x - 47);
+            let attr = self.set_text_colors("critical");
+            self.window.printw(" --- ");
+            self.window.attroff(attr);
+            let attr = self.set_text_colors("slowDecrease");
+            self.window.printw("  -  ");
+            self.window.attroff(attr);
+            let attr = self.set_text_colors("hold");
+            self.window.printw("     ");
+            self.window.attroff(attr);
+            let attr = self.set_text_colors("slowIncrease");
+            self.window.printw("  +  ");
+            self.window.attroff(attr);
+            let attr = self.set_text_colors("rampUp");
+            self.window.printw(" +++ ");
+            self.window.attroff(attr);
+            self.draw_staleness(0, 40);
+        } else {
+            self.window.printw(format!("Viewerator v{}", clap::crate_version!()));
+            self.draw_staleness(0, 20);
+        }
+        self.window.mv(1, 0);
+        self.window.hline(ACS_HLINE(), self.x);
+        if self.x >= 66 {
+            self.window
+                .mvprintw(0, self.x - 20, format!("Minerator: {}", self.wd.minerator));
+        }
+        let num_devices = self.wd.workers.len();
+        debug!("Numer of devices = {}", num_devices);
+        if num_devices != 1 {
+            self.draw_devices(1, if wide { 30 } else { 0 });
+        }
+    }
+
+    fn pages_log(&self) -> bool {
+        self.view == View::Device && self.layout().get(Panel::Log).is_some()
+    }
+
+    fn layout(&self) -> layout::Layout {
+        let w = self.wd.workers.get(self.current_worker);
+        let content = layout::Content {
+            bcu: matches!(w, Some(w) if w.hw_type == webdata::HWTYPE_BCU),
+            sparkline_width: self.trend_offset(),
+            sysmons: w.map_or(0, |w| w.sysmons.sysmon.len()),
+            hidden: self.config.layout.hidden.clone(),
+            zoom: self.config.layout.zoom,
+        };
+        // the bottom row is the footer
+        layout::Layout::new(self.x, self.y - 1, &content)
+    }
+
+    //
+    // The board's power and vccint readings, with the device efficiency last.
+    //
+    fn draw_sensors(&self, y: i32, x: i32, w: &webdata::Worker, prefix: usize) {
+        let sw = self.trend_offset();
+        self.window.mv(y, x);
+        self.window.hline(ACS_HLINE(), 24 + sw);
+        self.draw_trend_heading(y, x + 25);
+        let rows = [
+            (
+                "Input Power",
+                w.input_power,
+                w.input_power_health.as_str(),
+                Metric::InputPower,
+            ),
+            ("AUX 12V", w.aux_12v, w.aux_12v_health.as_str(), Metric::Aux12V),
+            (
+                "AUX Current",
+                w.aux_current,
+                w.aux_current_health.as_str(),
+                Metric::AuxCurrent,
+            ),
+            ("PEX 12V", w.pex_12v, w.pex_12v_health.as_str(), Metric::Pex12V),
+            (
+                "PEX Current",
+                w.pex_current,
+                w.pex_current_health.as_str(),
+                Metric::PexCurrent,
+            ),
+            ("VCCINT", w.vccint, "", Metric::Vccint),
+            (
+                "VCCINT Current",
+                w.vccint_current,
+                w.vccint_current_health.as_str(),
+                Metric::VccintCurrent,
+            ),
+            (
+                "VRCTRL Temp",
+                w.vrctrl_temp,
+                w.vrctrl_temp_health.as_str(),
+                Metric::VrctrlTemp,
+            ),
+        ];
+        for (row, (label, value, health, metric)) in rows.iter().enumerate() {
+            let row = y + 1 + row as i32;
+            self.window.mvprintw(row, x, format!("{:16}", label));
+            let attr = if *metric == Metric::VrctrlTemp {
+                self.set_temperature_colors(*value, health)
+            } else {
+                self.set_text_colors(health)
+            };
+            self.window
+                .mvprintw(row, x + 16, self.marked(Screen::float_to_string3(*value), health));
+            self.window.attroff(attr);
+            self.draw_trend(row, x + 25, SPARKLINE_WIDTH, &w.hw_uid, metric);
+        }
+
+        self.window
+            .mvprintw(y + 9, x, format!("Efficiency {:5}", units::label(prefix, "/J")));
+        let efficiency = metrics::device_efficiency(w)
+            .minute
+            .map(|e| units::scale_stat(e, prefix));
+        self.window
+            .mvprintw(y + 9, x + 16, Screen::option_to_string1(efficiency));
+    }
+
+    //
+    // The events seen between responses, newest at the bottom, next to the log.
+    // '[' and ']' scroll back through older ones.
+    //
+    fn draw_events(&mut self, r: layout::Rect) {
+        let (y, x) = (r.y, r.x);
+        let width = r.width as usize;
+        let lines = (r.height - 1).max(0) as usize;
+        for row in y..r.bottom() {
+            // separated from the log when beside it
+            if x > 0 {
+                self.window.mv(row, x - 1);
+                self.window.addch(pancurses::ACS_VLINE());
+            }
+            self.window.mvprintw(row, x, format!("{:w$}", "", w = width));
+        }
+        self.target(r, Target::Events);
+        let events = self.wd.events.events();
+        self.event_scroll = self.event_scroll.min(events.len().saturating_sub(lines));
+        let end = events.len() - self.event_scroll;
+        let start = end.saturating_sub(lines);
+        let title = if self.event_scroll > 0 {
+            format!(
+                "Events ({} newer below, {} to scroll)",
+                self.event_scroll,
+                self.keymap.describe(keymap::Action::NewerEvents)
+            )
+        } else {
+            format!("Events ({})", events.len())
+        };
+        self.window.mvprintw(y, x, format!("{:.w$}", title, w = width));
+        for (row, event) in events.range(start..end).enumerate() {
+            let text = format!("{} {} {}", event.timestamp(), event.device, event.message);
+            let attr = self.set_text_colors(&event.level);
+            self.window
+                .mvprintw(y + 1 + row as i32, x, format!("{:.w$}", text, w = width));
+            self.window.attroff(attr);
+        }
+    }
+
+    //
+    // Handles the keys that only mean something on the chart, returning whether
+    // the key was one of them.
+    //
+    fn chart_key(&mut self, c: char) -> bool {
+        match c {
+            'm' | 'M' => {
+                let metrics = match self.wd.workers.get(self.current_worker) {
+                    Some(w) => self.wd.history.metrics(&w.hw_uid),
+                    None => return false,
+                };
+                if metrics.is_empty() {
+                    return false;
+                }
+                let next = match metrics.iter().position(|m| *m == self.chart_metric) {
+                    Some(i) if c == 'm' => (i + 1) % metrics.len(),
+                    Some(i) => (i + metrics.len() - 1) % metrics.len(),
+                    None => 0,
+                };
+                self.chart_metric = metrics[next].clone();
+            }
+            'w' => self.chart_window = (self.chart_window + 1) % CHART_WINDOWS.len(),
+            'o' => {
+                self.chart_overlay = !self.chart_overlay;
+                self.chart_fleet = false;
+            }
+            'a' => {
+                self.chart_fleet = !self.chart_fleet;
+                self.chart_overlay = false;
+            }
+            _ => return false,
+        }
+        info!(
+            "Charting {} over {}, overlay {}, fleet {}",
+            self.chart_metric.label(),
+            CHART_WINDOWS[self.chart_window].0,
+            self.chart_overlay,
+            self.chart_fleet
+        );
+        true
+    }
+
+    //
+    // Full screen plot of the chart metric for the current device, every device
+    // overlaid, or the fleet as a whole.
+    //
+    fn draw_chart(&self, y: i32) {
+        for row in y..self.y {
+            self.window.mv(row, 0);
+            self.window.clrtoeol();
+        }
+        let (window_name, window_secs) = CHART_WINDOWS[self.chart_window];
+        let end = SystemTime::now();
+        let start = end - Duration::from_secs(window_secs);
+        let plot_width = (self.x - CHART_AXIS_WIDTH).max(1) as usize;
+        let plot_height = (self.y - y - 4).max(1) as usize;
+
+        let devices: Vec<(usize, &webdata::Worker)> = self
+            .wd
+            .workers
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.chart_overlay || self.chart_fleet || *i == self.current_worker)
+            .collect();
+        let mut series: Vec<Vec<Option<f64>>> = devices
+            .iter()
+            .map(|(_, w)| {
+                let samples = self.wd.history.since(&w.hw_uid, &self.chart_metric, start);
+                sparkline::bucket(&samples, start, end, plot_width * 2)
+            })
+            .collect();
+        let title = if self.chart_fleet {
+            series = vec![chart::combine(&series, self.chart_metric.is_additive())];
+            "fleet".to_string()
+        } else if self.chart_overlay {
+            "all devices".to_string()
+        } else {
+            match devices.first() {
+                Some((i, w)) => format!("device {} {}", i + 1, w.name),
+                None => "no device".to_string(),
+            }
+        };
+        self.window.mvprintw(
+            y,
+            0,
+            format!("{} for {}, last {}", self.chart_metric.label(), title, window_name),
+        );
+        if self.chart_overlay {
+            self.window.printw("   ");
+            for (n, (i, _)) in devices.iter().enumerate() {
+                let attr = Screen::chart_color(n);
+                self.window.attron(attr);
+                self.window.printw(format!("{} ", i + 1));
+                self.window.attroff(attr);
+            }
+        }
+
+        let values: Vec<f64> = series.iter().flatten().filter_map(|v| *v).collect();
+        let summary = match chart::summarize(&values) {
+            Some(summary) => summary,
+            None => {
+                self.window.mvprintw(y + 2, 0, "No samples yet for this window");
+                return;
+            }
+        };
+        self.window.mvprintw(
+            y + 1,
+            0,
+            format!(
+                "min {}   max {}   avg {}",
+                Screen::float_to_string1(summary.min as f32),
+                Screen::float_to_string1(summary.max as f32),
+                Screen::float_to_string1(summary.avg as f32)
+            ),
+        );
+
+        let mut canvas = chart::Canvas::new(plot_width, plot_height);
+        for (n, values) in series.iter().enumerate() {
+            canvas.plot(n, values, summary.min, summary.max);
+        }
+        let top = y + 2;
+        for (row, cells) in canvas.rows().iter().enumerate() {
+            let row_y = top + row as i32;
+            self.window.mv(row_y, CHART_AXIS_WIDTH - 1);
+            self.window.addch(pancurses::ACS_VLINE());
+            for cell in cells {
+                let attr = match cell.series {
+                    Some(n) if self.chart_overlay => Screen::chart_color(n),
+                    _ => pancurses::A_NORMAL,
+                };
+                self.window.attron(attr);
+                self.window.addstr(cell.ch.to_string());
+                self.window.attroff(attr);
+            }
+        }
+        let bottom = top + plot_height as i32 - 1;
+        self.window
+            .mvprintw(top, 0, Screen::float_to_string1(summary.max as f32));
+        self.window.mvprintw(
+            (top + bottom) / 2,
+            0,
+            Screen::float_to_string1(((summary.min + summary.max) / 2.0) as f32),
+        );
+        self.window
+            .mvprintw(bottom, 0, Screen::float_to_string1(summary.min as f32));
+        self.window.mv(bottom + 1, CHART_AXIS_WIDTH - 1);
+        self.window.addch(pancurses::ACS_LLCORNER());
+        self.window.hline(ACS_HLINE(), plot_width as i32);
+        self.window
+            .mvprintw(bottom + 2, CHART_AXIS_WIDTH, format!("-{}", window_name));
+        self.window.mvprintw(bottom + 2, self.x - 4, "now");
+    }
+
+    //
+    // A box over whatever is showing with every key that does something here,
+    // what the health colors mean, and where the data is coming from, in as many
+    // columns as fit.  The arrows and page keys scroll it, any other key closes
+    // it.
+    //
+    fn draw_help<'a>(&mut self, matches: &clap::ArgMatches<'a>) {
+        let r = match layout::help_box(self.x, self.y) {
+            Some(r) => r,
+            None => return,
+        };
+        let lines = self.help_lines(matches);
+        let columns = layout::Columns::new(r, layout::HELP_COLUMN_WIDTH);
+        let max_scroll = columns.max_scroll(lines.len());
+        self.help_scroll = self.help_scroll.min(max_scroll);
+        let title = if max_scroll > 0 {
+            " Help, arrows scroll, any other key closes "
+        } else {
+            " Help, any key closes "
+        };
+        self.draw_box(r.y, r.x, r.height, r.width, title);
+        let width = columns.width as usize;
+        for (i, (line, level)) in lines.iter().enumerate() {
+            let (row, column) = match columns.place(i, self.help_scroll) {
+                Some(place) => place,
+                None => continue,
+            };
+            let (y, x) = (r.y + 1 + row, r.x + 2 + column);
+            match level {
+                // the level's name in its colors, then what it means
+                Some(level) => {
+                    let (name, meaning) = line.split_at(line.len().min(14));
+                    let attr = self.set_text_colors(level);
+                    self.window.mvprintw(y, x, name);
+                    self.window.attroff(attr);
+                    self.window
+                        .printw(format!("{:w$.w$}", meaning, w = width.saturating_sub(name.len())));
+                }
+                None => {
+                    self.window.mvprintw(y, x, format!("{:w$.w$}", line, w = width));
+                }
+            }
+        }
+        if self.help_scroll < max_scroll {
+            self.window.mvprintw(r.bottom() - 1, r.right() - 12, " more ");
+        }
+    }
+
+    //
+    // Every line of the help, with the health level the legend lines show.
+    //
+    fn help_lines<'a>(&self, matches: &clap::ArgMatches<'a>) -> Vec<(String, Option<&'static str>)> {
+        let mut keys: Vec<(String, String)> = keymap::ACTIONS
+            .iter()
+            .map(|action| (self.keymap.describe(*action), action.description().to_string()))
+            .collect();
+        keys.push(("1-9".to_string(), "show that device".to_string()));
+        for (key, description) in self.view_keys() {
+            keys.push((key.to_string(), description.to_string()));
+        }
+        let mut lines: Vec<(String, Option<&'static str>)> = keys
+            .iter()
+            .map(|(key, description)| (format!("{:16.16} {}", key, description), None))
+            .collect();
+        lines.push((String::new(), None));
+        lines.push(("Health, worst to best".to_string(), None));
+        for level in health::LEVELS.iter() {
+            lines.push((format!(" {:12}  {}", level, health::meaning(level)), Some(*level)));
+        }
+        lines.push((String::new(), None));
+        let source = match matches.value_of("input_file") {
+            Some(file) => format!("file {}", file),
+            None => self.wd.host.clone(),
+        };
+        let updated = match self.wd.age() {
+            Some(age) if self.wd.stale => format!("stale, last seen {} ago", Screen::duration_to_string(age)),
+            Some(age) => format!("{} ago", Screen::duration_to_string(age)),
+            None => "not yet".to_string(),
+        };
+        let data = [
+            "Data".to_string(),
+            format!(" source    {}", source),
+            format!(" minerator {}", self.wd.minerator),
+            format!(" updated   {}", updated),
+            format!(" polling   {}", if self.paused { "paused" } else { "every second" }),
+            format!(" log       {}", log_display::LOG_FILE),
+        ];
+        lines.extend(data.iter().map(|line| (line.clone(), None)));
+        lines
+    }
+
+    //
+    // Clears a rectangle and draws a line around it with the title on top.
+    //
+    fn draw_box(&self, y: i32, x: i32, height: i32, width: i32, title: &str) {
+        for row in y..y + height {
+            self.window.mvprintw(row, x, format!("{:w$}", "", w = width as usize));
+        }
+        self.window.mv(y, x);
+        self.window.addch(pancurses::ACS_ULCORNER());
+        self.window.hline(ACS_HLINE(), width - 2);
+        self.window.mv(y, x + width - 1);
+        self.window.addch(pancurses::ACS_URCORNER());
+        self.window.mv(y + 1, x);
+        self.window.vline(pancurses::ACS_VLINE(), height - 2);
+        self.window.mv(y + 1, x + width - 1);
+        self.window.vline(pancurses::ACS_VLINE(), height - 2);
+        self.window.mv(y + height - 1, x);
+        self.window.addch(pancurses::ACS_LLCORNER());
+        self.window.hline(ACS_HLINE(), width - 2);
+        self.window.mv(y + height - 1, x + width - 1);
+        self.window.addch(pancurses::ACS_LRCORNER());
+        self.window.mvprintw(y, x + 2, title);
+    }
+
+    //
+    // Keys that only do something in the view showing.
+    //
+    fn view_keys(&self) -> Vec<(&'static str, &'static str)> {
+        match self.view {
+            View::Chart => vec![
+                ("m/M", "next/previous metric"),
+                ("w", "time window"),
+                ("o", "overlay devices"),
+                ("a", "fleet total"),
+            ],
+            View::Fleet => vec![("</>", "sort column"), ("-", "reverse sort")],
+            _ => vec![],
+        }
+    }
+
+    //
+    // Hints for the keys that matter most in the view showing, as many as fit
+    // on the bottom row.
+    //
+    fn draw_footer(&self) {
+        use keymap::Action;
+        let key = |action| {
+            self.keymap
+                .keys(action)
+                .first()
+                .map(keymap::key_name)
+                .unwrap_or_default()
+        };
+        let mut hints: Vec<(String, &str)> = vec![(key(Action::Help), "help")];
+        let back = |action| (key(action), "back");
+        match self.view {
+            View::Device => {
+                hints.push((
+                    format!("{}/{}", key(Action::PreviousDevice), key(Action::NextDevice)),
+                    "device",
+                ));
+                hints.push((key(Action::Search), "find"));
+                hints.push((
+                    format!("{}/{}", key(Action::PreviousPage), key(Action::NextPage)),
+                    "log page",
+                ));
+                hints.push((key(Action::LogSearch), "search log"));
+                hints.push((key(Action::LogFilter), "filter log"));
+                hints.push((
+                    key(Action::Follow),
+                    if self.log.follow { "pause log" } else { "follow log" },
+                ));
+                hints.push((key(Action::Chart), "chart"));
+                hints.push((key(Action::Fleet), "fleet"));
+                hints.push((key(Action::Health), "health"));
+                hints.push((
+                    key(Action::Session),
+                    if self.show_session { "sparklines" } else { "min/max" },
+                ));
+                if self.show_session {
+                    hints.push((key(Action::ResetSession), "reset min/max"));
+                }
+                hints.push((key(Action::StatsMode), "stats"));
+                hints.push((key(Action::TogglePane), "events"));
+                hints.push((
+                    key(Action::Zoom),
+                    if self.config.layout.zoom.is_some() {
+                        "next zoom"
+                    } else {
+                        "zoom"
+                    },
+                ));
+                hints.push((key(Action::Pause), if self.paused { "resume" } else { "pause" }));
+            }
+            View::Chart => {
+                hints.extend(self.view_keys().iter().map(|(k, d)| (k.to_string(), *d)));
+                hints.push(back(Action::Chart));
+            }
+            View::Fleet => {
+                hints.extend(self.view_keys().iter().map(|(k, d)| (k.to_string(), *d)));
+                hints.push(back(Action::Fleet));
+            }
+            View::Health => hints.push(back(Action::Health)),
+        }
+        hints.push((key(Action::Quit), "quit"));
+
+        let mut footer = String::new();
+        for (key, description) in hints.iter().filter(|(key, _)| !key.is_empty()) {
+            let hint = format!("{} {}   ", key, description);
+            if footer.len() + hint.len() > self.x.max(0) as usize {
+                break;
+            }
+            footer.push_str(&hint);
+        }
+        self.window.mv(self.y - 1, 0);
+        self.window.clrtoeol();
+        self.window.attron(pancurses::A_DIM);
+        self.window.mvprintw(self.y - 1, 0, footer.trim_end());
+        self.window.attroff(pancurses::A_DIM);
+    }
+
+    //
+    // Sort keys of the fleet table, true if the key was one of them.
+    //
+    fn fleet_key(&mut self, c: char) -> bool {
+        match c {
+            '>' => self.fleet_sort = self.fleet_sort.next(),
+            '<' => self.fleet_sort = self.fleet_sort.previous(),
+            '-' => self.fleet_descending = !self.fleet_descending,
+            _ => return false,
+        }
+        info!(
+            "Sorting fleet by {} {}",
+            self.fleet_sort.label(),
+            if self.fleet_descending {
+                "descending"
+            } else {
+                "ascending"
+            }
+        );
+        true
+    }
+
+    //
+    // Every device in a row, sorted on the chosen column, so the hottest or
+    // worst card of a rig can be found at a glance.  Accepted rates are in the
+    // units of the current device so the rows compare.
+    //
+    fn draw_fleet(&self, y: i32) {
+        for row in y..self.y {
+            self.window.mv(row, 0);
+            self.window.clrtoeol();
+        }
+        let prefix = match self.wd.workers.get(self.current_worker) {
+            Some(w) => self.hash_prefix(w),
+            None => 0,
+        };
+        self.window.mvprintw(
+            y,
+            0,
+            format!(
+                "Fleet: {} devices, accepted in {}, sorted by {} {}",
+                self.wd.workers.len(),
+                units::label(prefix, "/s"),
+                self.fleet_sort.label(),
+                if self.fleet_descending {
+                    "descending"
+                } else {
+                    "ascending"
+                }
+            ),
+        );
+        let mut x = 0;
+        for column in fleet::COLUMNS.iter() {
+            let attr = if *column == self.fleet_sort {
+                pancurses::A_REVERSE
+            } else {
+                pancurses::A_NORMAL
+            };
+            self.window.attron(attr);
+            self.window.mvprintw(
+                y + 2,
+                x,
+                format!("{:w$}", column.label(), w = column.width() as usize - 1),
             );
-            error!(
-                "Console screen must be at least {} rows in Y, current Y is {}",
-                MIN_Y, self.y
+            self.window.attroff(attr);
+            self.target(
+                layout::Rect::new(y + 2, x, 1, column.width()),
+                Target::SortColumn(*column),
             );
-            exit(ExitCode::SUCCESS.report())
+            x += column.width();
+        }
+        self.window.mv(y + 3, 0);
+        self.window.hline(ACS_HLINE(), x);
+
+        let rows = fleet::rows(&self.wd.workers, self.fleet_sort, self.fleet_descending);
+        for (i, row) in rows.iter().enumerate() {
+            let line = y + 4 + i as i32;
+            if line >= self.y - 1 {
+                break;
+            }
+            let w = &self.wd.workers[row.index];
+            self.target(layout::Rect::new(line, 0, 1, self.x), Target::Device(row.index));
+            let mut x = 0;
+            for column in fleet::COLUMNS.iter() {
+                let width = column.width() as usize - 1;
+                let mut health = "";
+                let mut temperature = None;
+                let text = match column {
+                    fleet::Column::Index => format!("{:>w$}", row.index + 1, w = width),
+                    fleet::Column::Name => format!("{:w$.w$}", row.name, w = width),
+                    fleet::Column::HwUid => format!("{:w$.w$}", row.hw_uid, w = width),
+                    fleet::Column::Board => format!("{:w$}", row.board, w = width),
+                    fleet::Column::Health => {
+                        health = &row.health;
+                        format!("{:w$.w$}", row.health, w = width)
+                    }
+                    fleet::Column::Hottest => {
+                        health = Screen::hottest_health(w);
+                        temperature = row.hottest;
+                        format!("{:>w$}", Screen::option_to_string1(row.hottest), w = width)
+                    }
+                    fleet::Column::Power => {
+                        health = &w.input_power_health;
+                        format!("{:>w$}", Screen::float_to_string1(row.power), w = width)
+                    }
+                    fleet::Column::Multiplier => {
+                        format!("{:>w$}", Screen::option_to_string1(row.multiplier), w = width)
+                    }
+                    fleet::Column::Accepted => {
+                        let rate = units::scale_stat(row.accepted, prefix);
+                        format!("{:>w$}", units::compact(rate), w = width)
+                    }
+                    fleet::Column::BadNonce => {
+                        let text = match row.bad_nonce {
+                            Some(percent) => {
+                                health = self.config.bad_nonce_percent.health(percent);
+                                format!("{:.1}%", percent)
+                            }
+                            None => "-".to_string(),
+                        };
+                        format!("{:>w$}", text, w = width)
+                    }
+                };
+                let mut attr = match temperature {
+                    Some(t) => self.set_temperature_colors(t, health),
+                    None => self.set_text_colors(health),
+                };
+                if row.index == self.current_worker && *column == fleet::Column::Index {
+                    attr |= pancurses::A_BOLD | pancurses::A_UNDERLINE;
+                    self.window.attron(attr);
+                }
+                self.window.mvprintw(line, x, text);
+                self.window.attroff(attr);
+                x += column.width();
+            }
+        }
+    }
+
+    //
+    // Health of the hottest sysmon, which is what the Hottest column shows.
+    //
+    fn hottest_health(w: &webdata::Worker) -> &str {
+        w.sysmons
+            .sysmon
+            .iter()
+            .max_by(|a, b| {
+                a.temperature
+                    .partial_cmp(&b.temperature)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map_or("", |s| s.health.as_str())
+    }
+
+    //
+    // Share of the session each health field of the current device spent at
+    // each level, and how many times it changed level.  The sensor name is
+    // colored with the level it is at now.
+    //
+    fn draw_health(&self, y: i32) {
+        let w = match self.wd.workers.get(self.current_worker) {
+            Some(w) => w,
+            None => return,
+        };
+        self.window
+            .mvprintw(y, 0, format!("Health residency this session: {}  {}", w.name, w.hw_uid));
+        self.window.mvprintw(y + 2, 0, format!("{:16}", "Sensor"));
+        for (i, level) in health::LEVELS.iter().enumerate() {
+            let attr = self.set_text_colors(level);
+            self.window
+                .mvprintw(y + 2, 16 + 14 * i as i32, format!("{:>13}", level));
+            self.window.attroff(attr);
+        }
+        self.window.mvprintw(y + 2, 86, format!("{:>8}", "Changes"));
+        self.window.mv(y + 3, 0);
+        self.window.hline(ACS_HLINE(), 94);
+        let fields = match self.wd.health.device(&w.hw_uid) {
+            Some(fields) => fields,
+            None => return,
+        };
+        let tracked = fields.values().map(|r| r.total()).max().unwrap_or_default();
+        for (row, (field, residency)) in fields.iter().enumerate() {
+            let row = y + 4 + row as i32;
+            if row >= self.y - 1 {
+                break;
+            }
+            let attr = self.set_text_colors(residency.level().unwrap_or(""));
+            self.window.mvprintw(row, 0, format!("{:15}", field.label()));
+            self.window.attroff(attr);
+            for (i, level) in health::LEVELS.iter().enumerate() {
+                let percent = match residency.percent(level) {
+                    Some(percent) => format!("{:>12.1}%", percent),
+                    None => format!("{:>13}", "-"),
+                };
+                self.window.mvprintw(row, 16 + 14 * i as i32, percent);
+            }
+            self.window.mvprintw(row, 86, format!("{:>8}", residency.transitions));
+        }
+        self.window
+            .mvprintw(y + 1, 0, format!("tracked for {}", Screen::duration_to_string(tracked)));
+    }
+
+    fn chart_color(n: usize) -> pancurses::chtype {
+        pancurses::COLOR_PAIR((CHART_COLOR_BASE as usize + n % CHART_COLORS.len()) as pancurses::chtype)
+    }
+
+    //
+    // How much the session columns or the sparklines widen each panel.
+    //
+    fn trend_offset(&self) -> i32 {
+        if self.show_session {
+            SESSION_WIDTH as i32 + 1
+        } else if self.show_sparklines {
+            SPARKLINE_WIDTH as i32 + 1
+        } else {
+            0
         }
-        debug!("Screen is {} X x {} Y", self.x, self.y);
-
-        start_color();
-        init_pair(HEALTH_COLOR_RAMPUP, pancurses::COLOR_BLACK, pancurses::COLOR_GREEN);
-        init_pair(HEALTH_COLOR_SLOWINCREASE, pancurses::COLOR_BLACK, pancurses::COLOR_CYAN);
-        init_pair(HEALTH_COLOR_HOLD, pancurses::COLOR_BLACK, pancurses::COLOR_YELLOW);
-        init_pair(
-            HEALTH_COLOR_SLOWDECREASE,
-            pancurses::COLOR_WHITE,
-            pancurses::COLOR_MAGENTA,
-        );
-        init_pair(HEALTH_COLOR_CRITICAL, pancurses::COLOR_WHITE, pancurses::COLOR_RED);
+    }
 
-        pancurses::set_title(&*format!("Viewerator v{}", clap::crate_version!()));
-        self.window.keypad(true);
-        self.window.timeout(1000);
-        cbreak();
-        noecho();
+    //
+    // Next to a value, its session min and max when they're on, otherwise its
+    // sparkline if those are.
+    //
+    fn draw_trend(&self, y: i32, x: i32, width: usize, hw_uid: &str, metric: &Metric) {
+        if self.show_session {
+            let range = match self.wd.session.get(hw_uid, metric) {
+                Some(running) => format!("{:>5} {:>5}", units::short(running.min), units::short(running.max)),
+                None => String::new(),
+            };
+            self.window.mvprintw(y, x, format!("{:w$}", range, w = SESSION_WIDTH));
+            return;
+        }
+        if self.show_sparklines {
+            self.window.mvprintw(y, x, self.sparkline(hw_uid, metric, width));
+        }
+    }
 
-        self.window.refresh();
+    fn sparkline(&self, hw_uid: &str, metric: &Metric, width: usize) -> String {
+        let end = SystemTime::now();
+        let start = end - Duration::from_secs(self.config.sparkline_minutes * 60);
+        let samples = self.wd.history.since(hw_uid, metric, start);
+        sparkline::sparkline(&samples, start, end, width)
     }
 
-    pub fn mainloop<'a>(&mut self, matches: &clap::ArgMatches<'a>) {
-        let mut ld = log_display::LogDisplay::new();
-        loop {
-            match self.window.getch() {
-                Some(Input::Character(c)) => {
-                    if c.is_ascii_digit() {
-                        let w: usize = c.to_digit(10).unwrap().try_into().unwrap();
-                        if w <= self.wd.workers.len() {
-                            if self.wd.workers[self.current_worker].hw_type != self.wd.workers[w - 1].hw_type {
-                                self.window.erase();
-                            }
-                            self.current_worker = w - 1;
-                            info!("Showing device {}", w - 1);
-                            self.update_screen(&matches, &mut ld);
-                        }
-                    }
+    //
+    // A zoomed panel, one reading a row with its history drawn across the rest
+    // of the screen, after the session min and max when they're on.
+    //
+    fn draw_detail(&self, r: layout::Rect, hw_uid: &str, rows: &[Detail]) {
+        let session = if self.show_session { SESSION_WIDTH as i32 + 1 } else { 0 };
+        let trend_width = (r.width - DETAIL_TREND_X - session).max(0) as usize;
+        for (i, row) in rows.iter().take(r.height.max(0) as usize).enumerate() {
+            let y = r.y + i as i32;
+            self.window.mv(y, r.x);
+            self.window.clrtoeol();
+            self.window.mvprintw(y, r.x, &row.label);
+            match row.temperature {
+                Some((value, health)) => {
+                    let attr = self.set_temperature_colors(value, health);
+                    self.window
+                        .mvprintw(y, r.x + 16, self.marked(row.value.clone(), health));
+                    self.window.attroff(attr);
                 }
-                Some(Input::KeyDC) => break,
-                Some(_input) => {} // ignore
                 None => {
-                    self.update_screen(&matches, &mut ld);
+                    self.window.mvprintw(y, r.x + 16, &row.value);
                 }
             }
-            self.window.refresh();
+            if let Some(metric) = &row.metric {
+                if self.show_session {
+                    self.draw_trend(y, r.x + DETAIL_TREND_X, SESSION_WIDTH, hw_uid, metric);
+                }
+                self.window.mvprintw(
+                    y,
+                    r.x + DETAIL_TREND_X + session,
+                    self.sparkline(hw_uid, metric, trend_width),
+                );
+            }
         }
-        info!("Exiting..");
     }
 
-    pub fn update_screen<'a>(&mut self, matches: &clap::ArgMatches<'a>, ld: &mut log_display::LogDisplay) {
-        debug!("Getting data");
-        self.wd.getdata(matches);
-        let loginfo = ld.read_raw();
-        debug!("Updating screen");
-        self.window.printw(format!(
-            "Viewerator v{}, press delete to exit    ",
-            clap::crate_version!()
-        ));
-        self.window.mv(0, self.x - 47);
-        let attr = self.set_text_colors(&"critical".to_string());
-        self.window.printw(" --- ");
-        self.window.attroff(attr);
-        let attr = self.set_text_colors(&"slowDecrease".to_string());
-        self.window.printw("  -  ");
-        self.window.attroff(attr);
-        let attr = self.set_text_colors(&"hold".to_string());
-        self.window.printw("     ");
-        self.window.attroff(attr);
-        let attr = self.set_text_colors(&"slowIncrease".to_string());
-        self.window.printw("  +  ");
-        self.window.attroff(attr);
-        let attr = self.set_text_colors(&"rampUp".to_string());
-        self.window.printw(" +++ \n");
-        self.window.attroff(attr);
-
-        self.window.hline(ACS_HLINE(), self.x);
-
-        self.window
-            .mvprintw(0, self.x - 20, format!("Minerator: {}", self.wd.minerator));
-        let num_devices = self.wd.workers.len();
-        debug!("Numer of devices = {}", num_devices);
-        if num_devices != 1 {
-            self.draw_devices(1, 30);
+    fn sysmon_details(sysmons: &webdata::SysMons) -> Vec<Detail<'_>> {
+        let mut rows = vec![];
+        for (num, sysmon) in sysmons.sysmon.iter().enumerate() {
+            rows.push(Detail::heading(format!("Sysmon {}", num)));
+            rows.push(Detail::temperature(
+                sysmon.temperature,
+                &sysmon.health,
+                Metric::SysmonTemperature(num),
+            ));
+            rows.push(Detail::reading("vccint", sysmon.vccint, Metric::SysmonVccint(num)));
+            rows.push(Detail::reading("vccaux", sysmon.vccaux, Metric::SysmonVccaux(num)));
+            rows.push(Detail::reading("vccbram", sysmon.vccbram, Metric::SysmonVccbram(num)));
         }
-        for (i, w) in self.wd.workers.iter().enumerate() {
-            if i == self.current_worker {
-                self.window.mvprintw(2, 0, format!("HWUID: {}", w.dna));
-                self.window.mvprintw(3, 0, format!("Name:  {}", w.name));
-
-                self.window.mv(4, 0);
-                self.window.hline(ACS_HLINE(), 24);
-                self.window.mvprintw(5, 0, "Input Power    ");
-                let attr = self.set_text_colors(&w.input_power_health);
-                self.window.mvprintw(5, 16, Screen::float_to_string3(w.input_power));
-                self.window.attroff(attr);
+        rows
+    }
 
-                self.window.mvprintw(6, 0, "AUX 12V");
-                let attr = self.set_text_colors(&w.aux_12v_health);
-                self.window.mvprintw(6, 16, Screen::float_to_string3(w.aux_12v));
-                self.window.attroff(attr);
+    fn phase_details(w: &webdata::Worker) -> Vec<Detail<'_>> {
+        let phases = [
+            (
+                w.phase0_status_global,
+                w.phase0_temperature,
+                &w.phase0_temperature_health,
+                w.phase0_vout,
+                Metric::Phase0Temperature,
+                Metric::Phase0Vout,
+            ),
+            (
+                w.phase1_status_global,
+                w.phase1_temperature,
+                &w.phase1_temperature_health,
+                w.phase1_vout,
+                Metric::Phase1Temperature,
+                Metric::Phase1Vout,
+            ),
+        ];
+        let mut rows = vec![];
+        for (num, (status, temperature, health, vout, temperature_metric, vout_metric)) in
+            phases.iter().cloned().enumerate()
+        {
+            rows.push(Detail::heading(format!("LTC3884 Phase {}", num)));
+            rows.push(Detail {
+                value: format!("{:#08x}", status),
+                ..Detail::heading("  Global status".to_string())
+            });
+            rows.push(Detail::temperature(temperature, health, temperature_metric));
+            rows.push(Detail::reading("vout", vout, vout_metric));
+        }
+        rows.push(Detail::heading("VRCTRL".to_string()));
+        rows.push(Detail::temperature(
+            w.vrctrl_temp,
+            &w.vrctrl_temp_health,
+            Metric::VrctrlTemp,
+        ));
+        rows
+    }
 
-                self.window.mvprintw(7, 0, "AUX Current");
-                let attr = self.set_text_colors(&w.aux_current_health);
-                self.window.mvprintw(7, 16, Screen::float_to_string3(w.aux_current));
-                self.window.attroff(attr);
+    //
+    // Heads the session columns from the line over a panel.
+    //
+    fn draw_trend_heading(&self, y: i32, x: i32) {
+        if self.show_session {
+            self.window.mvprintw(y, x, format!("{:>5} {:>5}", "min", "max"));
+        }
+    }
 
-                self.window.mvprintw(8, 0, "PEX 12V");
-                let attr = self.set_text_colors(&w.pex_12v_health);
-                self.window.mvprintw(8, 16, Screen::float_to_string3(w.pex_12v));
+    fn draw_staleness(&self, y: i32, x: i32) {
+        match self.wd.age() {
+            Some(age) if self.wd.stale => {
+                let attr = self.set_text_colors("critical");
+                self.window
+                    .mvprintw(y, x, format!(" STALE, {} old ", Screen::duration_to_string(age)));
                 self.window.attroff(attr);
-
-                self.window.mvprintw(9, 0, "PEX Current");
-                let attr = self.set_text_colors(&w.pex_current_health);
-                self.window.mvprintw(9, 16, Screen::float_to_string3(w.pex_current));
+            }
+            _ if self.paused => {
+                let attr = self.set_text_colors("hold");
+                self.window.mvprintw(y, x, format!("{:24}", " PAUSED "));
                 self.window.attroff(attr);
+            }
+            _ => {
+                self.window.mvprintw(y, x, format!("{:24}", ""));
+            }
+        }
+    }
 
-                self.window.mvprintw(10, 0, "VCCINT");
-                self.window.mvprintw(10, 16, Screen::float_to_string3(w.vccint));
+    fn duration_to_string(d: Duration) -> String {
+        let secs = d.as_secs();
+        if secs < 60 {
+            format!("{}s", secs)
+        } else if secs < 3600 {
+            format!("{}m {:02}s", secs / 60, secs % 60)
+        } else if secs < 86400 {
+            format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+        } else {
+            format!("{}d {:02}h", secs / 86400, (secs % 86400) / 3600)
+        }
+    }
 
-                self.window.mvprintw(11, 0, "VCCINT Current");
-                let attr = self.set_text_colors(&w.vccint_current_health);
-                self.window.mvprintw(11, 16, Screen::float_to_string3(w.vccint_current));
-                self.window.attroff(attr);
+    //
+    // A stats table cell, either the rate itself, what it comes to per watt or
+    // the count the rate was worked out from, in the units of prefix.
+    //
+    fn stat_value(&self, rate: Option<f32>, count: Option<f32>, power: f32, prefix: usize) -> String {
+        let value = match self.stats_mode {
+            StatsMode::Rate => rate,
+            StatsMode::Efficiency => rate.and_then(|rate| metrics::efficiency(rate, power)),
+            StatsMode::Count => count,
+        };
+        Screen::option_to_string1(value.map(|v| units::scale_stat(v, prefix)))
+    }
 
-                self.window.mvprintw(12, 0, "VRCTRL Temp");
-                let attr = self.set_text_colors(&w.vrctrl_temp_health);
-                self.window.mvprintw(12, 16, Screen::float_to_string3(w.vrctrl_temp));
-                self.window.attroff(attr);
-                if w.hw_type == webdata::HWTYPE_BCU {
-                    self.draw_phases(4, 26, &w);
-                }
-                self.draw_sysmons(9, 50, &w.sysmons);
-                for (_num, core) in w.cores.cores.iter().enumerate() {
-                    self.draw_clock(4, 50, &core.clock);
-                    self.draw_stats(14, 0, &w, &core);
-                }
-            }
-        }
-        if !matches.is_present("input_file") {
-            self.window.mv(21, 0);
-            let lines_available: usize = (self.y - 21).try_into().unwrap();
-            let name = format!("{}: ", &self.wd.workers[self.current_worker].cores.cores[0].stats.name);
-            let match_this_board: Vec<&String> = loginfo
-                .iter()
-                .filter(|s| s.contains(&name) || s.contains("Fee"))
-                .collect();
-            let starting_index = match_this_board.len() - lines_available;
-            let display_lines = &match_this_board[starting_index..];
-            for line in display_lines {
-                let mut attr = pancurses::A_NORMAL;
-                if line.contains("WRN") {
-                    attr = pancurses::COLOR_PAIR(HEALTH_COLOR_HOLD.try_into().unwrap());
-                } else if line.contains("ERR") {
-                    attr = pancurses::COLOR_PAIR(HEALTH_COLOR_CRITICAL.try_into().unwrap());
-                }
-                self.window.attron(attr);
-                self.window.printw(format!("{}\n", line));
-                self.window.attroff(attr);
-            }
+    //
+    // Hashrate unit for a device, the one configured for its algorithm or else
+    // whatever keeps its calculated rate readable.
+    //
+    fn hash_prefix(&self, w: &webdata::Worker) -> usize {
+        let core = match w.cores.cores.first() {
+            Some(core) => core,
+            None => return 0,
+        };
+        if let Some(prefix) = self.config.hashrate_units.get(&core.algo).and_then(|u| units::parse(u)) {
+            return prefix;
         }
-        self.window.mv(self.y - 1, self.x - 1);
+        let rate = metrics::total_rate(&core.stats.total, core.stats.total.calculated);
+        units::auto(f64::from(rate) * metrics::HASHES_PER_STAT_UNIT)
     }
 
-    fn calc_total(stat: webdata::StatDetail, val: f32) -> f32 {
-        let div: f32 = ((stat.endTime - stat.startTime) / 1_000_000_000.0) as f32;
-        val / div
+    fn option_to_string1(f: Option<f32>) -> String {
+        match f {
+            Some(f) => Screen::float_to_string1(f),
+            None => format!("{:>7}", "-"),
+        }
     }
 
     fn float_to_string1(f: f32) -> String {
-        if f >= 0.0 && f < 1000.0 {
-            format!("{:>7.1}", f)
-        } else if f >= 1000.0 && f < 1_000_000.0 {
-            format!("{:>6.1}K", f / 1000.0)
-        } else if f >= 1_000_000.0 && f < 1_000_000_000.0 {
-            format!("{:>6.1}M", f / 1_000_000.0)
-        } else if f >= 1_000_000_000.0 && f < 1_000_000_000_000.0 {
-            format!("{:>6.1}G", f / 1_000_000_000.0)
-        } else {
-            "*******".to_string()
-        }
+        units::compact(f)
     }
 
     fn float_to_string3(f: f32) -> String {
@@ -259,6 +1709,11 @@ impl Screen {
     }
 
     fn set_text_colors(&self, health: &str) -> pancurses::chtype {
+        if self.monochrome {
+            let attr = theme::attributes(health);
+            self.window.attron(attr);
+            return attr;
+        }
         let mut attr = pancurses::A_COLOR;
         match &health[..] {
             "rampUp" => {
@@ -282,9 +1737,66 @@ impl Screen {
         attr
     }
 
+    //
+    // Temperatures the health has no complaint about are shaded cold to hot
+    // when the terminal has the colors for it, the rest get their health color.
+    //
+    fn set_temperature_colors(&self, value: f32, health: &str) -> pancurses::chtype {
+        if self.gradient_steps == 0 || !matches!(health, "" | "rampUp" | "slowIncrease") {
+            return self.set_text_colors(health);
+        }
+        let settings = &self.config.theme;
+        let step = theme::gradient_step(value, settings.cold, settings.hot, self.gradient_steps);
+        let attr = pancurses::COLOR_PAIR((GRADIENT_COLOR_BASE as usize + step) as pancurses::chtype);
+        self.window.attron(attr);
+        attr
+    }
+
+    //
+    // A right aligned value with the health marker in its padding when there
+    // is no color to show the health.
+    //
+    fn marked(&self, text: String, health: &str) -> String {
+        let marker = theme::marker(health);
+        if !self.monochrome || marker.is_empty() {
+            return text;
+        }
+        let padding = text.len() - text.trim_start().len();
+        let marker = &marker[..marker.len().min(padding)];
+        format!("{}{}{}", marker, &text[marker.len()..padding], text.trim_start())
+    }
+
+    //
+    // The device numbers colored by health with the current one highlighted.
+    // Rigs with more devices than fit scroll the bar to keep the current one
+    // in view, with < and > showing there are more.
+    //
     fn draw_devices(&self, y: i32, x: i32) {
-        self.window.mvprintw(y, x, " Current device is highlighted: ");
+        let count = self.wd.workers.len();
+        let mut x = x;
+        let mut room = self.x - x;
+        let title = " Current device is highlighted: ";
+        if room >= title.len() as i32 + 30 {
+            self.window.mvprintw(y, x, title);
+            x += title.len() as i32;
+            room -= title.len() as i32;
+        }
+        let slot = count.to_string().len() as i32 + 2;
+        let shown = fleet::visible(count, self.current_worker, ((room - 4) / slot).max(1) as usize);
+        self.window.mv(y, x);
+        self.window.printw(if shown.start > 0 { "< " } else { "  " });
+        if shown.start > 0 {
+            self.target(layout::Rect::new(y, x, 1, 1), Target::StepDevice(-DEVICE_PAGE));
+        }
         for (i, w) in self.wd.workers.iter().enumerate() {
+            if !shown.contains(&i) {
+                continue;
+            }
+            let digits = (i + 1).to_string().len() as i32;
+            self.target(
+                layout::Rect::new(y, self.window.get_cur_x(), 1, digits),
+                Target::Device(i),
+            );
             let mut attr = pancurses::A_NORMAL;
             if self.current_worker == i {
                 attr |= pancurses::A_BOLD | pancurses::A_UNDERLINE;
@@ -294,63 +1806,162 @@ impl Screen {
             self.window.attron(attr);
             self.window.printw(format!("{}", i + 1));
             self.window.attroff(attr);
-            self.window.printw("  ");
+            self.window
+                .printw(format!("{:w$}", "", w = slot as usize - (i + 1).to_string().len()));
+        }
+        if shown.end < count {
+            self.target(
+                layout::Rect::new(y, self.window.get_cur_x(), 1, 1),
+                Target::StepDevice(DEVICE_PAGE),
+            );
+            self.window.printw(">");
+        }
+    }
+
+    //
+    // Makes device i the one shown, clearing the screen if its board type lays
+    // out differently.
+    //
+    fn select_device(&mut self, i: usize) {
+        if i >= self.wd.workers.len() || i == self.current_worker {
+            return;
+        }
+        let hw_type = self.wd.workers.get(self.current_worker).map(|w| w.hw_type);
+        if hw_type != Some(self.wd.workers[i].hw_type) {
+            self.window.erase();
         }
+        self.current_worker = i;
+        self.log.reset();
+        info!("Showing device {}", i);
     }
 
-    fn draw_sysmons(&self, y: i32, x: i32, sysmons: &webdata::SysMons) {
+    //
+    // Moves the device shown by step, wrapping at either end for single steps
+    // and stopping there for pages.
+    //
+    fn step_device(&mut self, step: i32) {
+        let count = self.wd.workers.len() as i32;
+        if count == 0 {
+            return;
+        }
+        let current = self.current_worker as i32;
+        let next = if step.abs() == 1 {
+            (current + step).rem_euclid(count)
+        } else {
+            (current + step).max(0).min(count - 1)
+        };
+        self.select_device(next as usize);
+    }
+
+    //
+    // Reads a line typed on the bottom row, None if escape was pressed.
+    //
+    fn prompt(&self, label: &str) -> Option<String> {
+        let mut answer = String::new();
+        loop {
+            self.window.mv(self.y - 1, 0);
+            self.window.clrtoeol();
+            self.window.printw(format!("{}{}", label, answer));
+            self.window.refresh();
+            match self.window.getch() {
+                Some(Input::Character('\n')) | Some(Input::KeyEnter) => break,
+                Some(Input::Character('\u{1b}')) => {
+                    answer.clear();
+                    self.window.mv(self.y - 1, 0);
+                    self.window.clrtoeol();
+                    return None;
+                }
+                Some(Input::KeyBackspace) | Some(Input::Character('\u{7f}')) | Some(Input::Character('\u{8}')) => {
+                    answer.pop();
+                }
+                Some(Input::Character(c)) if !c.is_control() => answer.push(c),
+                _ => {}
+            }
+        }
+        self.window.mv(self.y - 1, 0);
+        self.window.clrtoeol();
+        Some(answer)
+    }
+
+    fn draw_sysmons(&self, y: i32, x: i32, hw_uid: &str, sysmons: &webdata::SysMons) {
         let line_length: i32 = (16 * sysmons.sysmon.len()).try_into().unwrap();
         self.window.mv(y, x);
         self.window.hline(ACS_HLINE(), line_length);
+        // with sparklines or session columns each value gets its own below it
+        let vccint_row = if self.trend_offset() > 0 { y + 4 } else { y + 3 };
         for (num, sysmon) in sysmons.sysmon.iter().enumerate() {
             let mut column_offset: i32 = (num * 16).try_into().unwrap();
             column_offset += x;
+            self.draw_trend_heading(y, column_offset);
             self.window.mvprintw(y + 1, column_offset, format!("Sysmon {}", num));
             self.window.mvprintw(y + 2, column_offset, "temp");
-            self.window.mvprintw(y + 3, column_offset, "vccint");
-            let attr = self.set_text_colors(&sysmon.health);
-            self.window
-                .mvprintw(y + 2, column_offset + 7, Screen::float_to_string3(sysmon.temperature));
+            self.window.mvprintw(vccint_row, column_offset, "vccint");
+            let attr = self.set_temperature_colors(sysmon.temperature, &sysmon.health);
+            self.window.mvprintw(
+                y + 2,
+                column_offset + 7,
+                self.marked(Screen::float_to_string3(sysmon.temperature), &sysmon.health),
+            );
             self.window.attroff(attr);
             self.window
-                .mvprintw(y + 3, column_offset + 7, Screen::float_to_string3(sysmon.vccint));
+                .mvprintw(vccint_row, column_offset + 7, Screen::float_to_string3(sysmon.vccint));
+            self.draw_trend(y + 3, column_offset, 15, hw_uid, &Metric::SysmonTemperature(num));
+            self.draw_trend(y + 5, column_offset, 15, hw_uid, &Metric::SysmonVccint(num));
         }
     }
 
     fn draw_phases(&self, y: i32, x: i32, w: &webdata::Worker) {
         self.window.mv(y, x);
-        self.window.hline(ACS_HLINE(), 22);
+        self.window.hline(ACS_HLINE(), 22 + self.trend_offset());
+        self.draw_trend_heading(y, x + 23);
 
         self.window.mvprintw(y + 1, x, "LTC3884 Phase 0");
         self.window.mvprintw(y + 2, x, "Global status");
         self.window
             .mvprintw(y + 2, x + 14, format!("{:#08x}", w.phase0_status_global));
         self.window.mvprintw(y + 3, x, "temperature");
-        let attr = self.set_text_colors(&w.phase0_temperature_health);
-        self.window
-            .mvprintw(y + 3, x + 14, Screen::float_to_string3(w.phase0_temperature));
+        let attr = self.set_temperature_colors(w.phase0_temperature, &w.phase0_temperature_health);
+        self.window.mvprintw(
+            y + 3,
+            x + 14,
+            self.marked(
+                Screen::float_to_string3(w.phase0_temperature),
+                &w.phase0_temperature_health,
+            ),
+        );
         self.window.attroff(attr);
+        self.draw_trend(y + 3, x + 23, SPARKLINE_WIDTH, &w.hw_uid, &Metric::Phase0Temperature);
         self.window.mvprintw(y + 4, x, "vout");
         self.window
             .mvprintw(y + 4, x + 14, Screen::float_to_string3(w.phase0_vout));
+        self.draw_trend(y + 4, x + 23, SPARKLINE_WIDTH, &w.hw_uid, &Metric::Phase0Vout);
 
         self.window.mvprintw(y + 5, x, "LTC3884 Phase 1");
         self.window.mvprintw(y + 6, x, "Global status");
         self.window
             .mvprintw(y + 6, x + 14, format!("{:#08x}", w.phase1_status_global));
         self.window.mvprintw(y + 7, x, "temperature");
-        let attr = self.set_text_colors(&w.phase1_temperature_health);
-        self.window
-            .mvprintw(y + 7, x + 14, Screen::float_to_string3(w.phase1_temperature));
+        let attr = self.set_temperature_colors(w.phase1_temperature, &w.phase1_temperature_health);
+        self.window.mvprintw(
+            y + 7,
+            x + 14,
+            self.marked(
+                Screen::float_to_string3(w.phase1_temperature),
+                &w.phase1_temperature_health,
+            ),
+        );
         self.window.attroff(attr);
+        self.draw_trend(y + 7, x + 23, SPARKLINE_WIDTH, &w.hw_uid, &Metric::Phase1Temperature);
         self.window.mvprintw(y + 8, x, "vout");
         self.window
             .mvprintw(y + 8, x + 14, Screen::float_to_string3(w.phase1_vout));
+        self.draw_trend(y + 8, x + 23, SPARKLINE_WIDTH, &w.hw_uid, &Metric::Phase1Vout);
     }
 
-    fn draw_clock(&self, y: i32, x: i32, clock: &webdata::Clock) {
+    fn draw_clock(&self, y: i32, x: i32, hw_uid: &str, clock: &webdata::Clock) {
         self.window.mv(y, x);
-        self.window.hline(ACS_HLINE(), 28);
+        self.window.hline(ACS_HLINE(), 28 + self.trend_offset());
+        self.draw_trend_heading(y, x + 29);
 
         self.window.mvprintw(y + 1, x, "Clock Multiplier");
         self.window.mvprintw(y + 2, x, "Bad Nonces");
@@ -363,76 +1974,289 @@ impl Screen {
         self.window
             .mvprintw(y + 3, x + 20, Screen::float_to_string3(clock.totalNonces));
         self.window.attroff(attr);
+        self.draw_trend(y + 1, x + 29, SPARKLINE_WIDTH, hw_uid, &Metric::Multiplier);
+        self.draw_trend(y + 2, x + 29, SPARKLINE_WIDTH, hw_uid, &Metric::BadNonces);
+        self.draw_trend(y + 3, x + 29, SPARKLINE_WIDTH, hw_uid, &Metric::TotalNonces);
+        self.draw_percent(
+            y + 2,
+            x + 29 + self.trend_offset(),
+            metrics::bad_nonce_percent(clock),
+            &self.config.bad_nonce_percent,
+        );
+    }
+
+    fn draw_fleet_efficiency(&self, y: i32, x: i32, prefix: usize) {
+        let efficiency = metrics::fleet_efficiency(&self.wd.workers);
+        let scaled = |e: Option<f32>| Screen::option_to_string1(e.map(|e| units::scale_stat(e, prefix)));
+        self.window.mvprintw(
+            y,
+            x,
+            format!(
+                "Fleet efficiency [{}] last minute {}  since start {}",
+                units::label(prefix, "/J"),
+                scaled(efficiency.minute),
+                scaled(efficiency.total)
+            ),
+        );
+    }
+
+    //
+    // Estimated earnings per day for the device and the whole rig, from the
+    // accepted rate since start and the power going in.
+    //
+    fn draw_profit(&self, y: i32, x: i32, w: &webdata::Worker) {
+        let profit = match &self.config.profit {
+            Some(profit) => profit,
+            None => return,
+        };
+        let (rate, _) = metrics::accepted_rates(std::slice::from_ref(w));
+        let device = profit.estimate(rate, w.input_power);
+        let (rate, _) = metrics::accepted_rates(&self.wd.workers);
+        let rig = profit.estimate(rate, metrics::fleet_power(&self.wd.workers));
+        self.window.mvprintw(
+            y,
+            x,
+            format!(
+                "Per day  revenue {:.2}  cost {:.2}  profit ",
+                device.revenue, device.cost
+            ),
+        );
+        self.draw_money(device.profit);
+        self.window.printw("  rig profit ");
+        self.draw_money(rig.profit);
+        self.window.printw("  ");
+    }
+
+    fn draw_regulator(&self, y: i32, x: i32, readings: &[regulator::Reading]) {
+        self.window.mv(y, x);
+        self.window.hline(ACS_HLINE(), REGULATOR_PANEL_WIDTH);
+        self.window.mvprintw(y + 1, x, "VCCINT Regulator");
+        for (row, reading) in readings.iter().enumerate() {
+            let row = y + 2 + row as i32;
+            self.window.mvprintw(row, x, format!("{:16}", reading.label));
+            let attr = self.set_text_colors(reading.health);
+            self.window
+                .mvprintw(row, x + 16, format!("{:>7.2}{}", reading.value, reading.unit));
+            self.window.attroff(attr);
+        }
+    }
+
+    //
+    // The worst regulator reading next to the device name, so a failing phase
+    // is flagged even when the panel doesn't fit.
+    //
+    fn draw_regulator_flag(&self, y: i32, x: i32, readings: &[regulator::Reading]) {
+        match regulator::worst(readings) {
+            Some(reading) => {
+                let attr = self.set_text_colors(reading.health);
+                self.window.mvprintw(
+                    y,
+                    x,
+                    format!("VR {} {:.1}{}", reading.label, reading.value, reading.unit),
+                );
+                self.window.attroff(attr);
+                self.window.printw("  ");
+            }
+            None => {
+                self.window.mvprintw(y, x, format!("{:25}", ""));
+            }
+        }
+    }
+
+    //
+    // Energy used today and in total, shortened to today's figures when it has
+    // to share the row.
+    //
+    fn draw_energy(&self, y: i32, x: i32, width: i32, w: &webdata::Worker) {
+        let energy = match &self.wd.energy {
+            Some(energy) => energy,
+            None => return,
+        };
+        let today = energy::local_date(SystemTime::now());
+        let mut text = format!(
+            "Energy [kWh] today {:.2}  total {:.2}   rig today {:.2}  total {:.2}",
+            energy.today(&w.hw_uid, &today),
+            energy.total(&w.hw_uid),
+            energy.rig_today(&today),
+            energy.rig_total()
+        );
+        if text.len() > width as usize {
+            text = format!(
+                "kWh today {:.1} rig {:.1}",
+                energy.today(&w.hw_uid, &today),
+                energy.rig_today(&today)
+            );
+        }
+        let width = width.max(0) as usize;
+        self.window.mvprintw(y, x, format!("{:w$.w$}", text, w = width));
+    }
+
+    fn draw_money(&self, amount: f64) {
+        let attr = self.set_text_colors(if amount < 0.0 { "critical" } else { "" });
+        self.window.printw(format!("{:.2}", amount));
+        self.window.attroff(attr);
+    }
+
+    //
+    // Where each block of stat columns starts and whether it is the last minute.
+    // Compact layouts only have room for the last minute.
+    //
+    fn stat_blocks(compact: bool) -> &'static [(i32, bool)] {
+        if compact {
+            &[(28, true)]
+        } else {
+            &[(28, false), (78, true)]
+        }
     }
 
-    fn draw_stats(&self, y: i32, x: i32, w: &webdata::Worker, core: &webdata::Core) {
+    fn draw_stats(&self, y: i32, x: i32, w: &webdata::Worker, core: &webdata::Core, compact: bool) {
         self.window.mvprintw(y, x, "Worker/Pool Name");
         self.window.mv(y + 2, x);
         self.window.hline(ACS_HLINE(), 27);
-        self.window.mvprintw(y, x + 28, "Since start [MH/s]");
-        self.window
-            .mvprintw(y + 1, x + 28, "WrkReq |Calcul |Found  |Valid  |Submit |Accept");
-        self.window.mv(y + 2, x + 28);
-        self.window.hline(ACS_HLINE(), 48);
-        self.window.mvprintw(y, x + 78, "Last Minute [MH/s]");
-        self.window
-            .mvprintw(y + 1, x + 78, "WrkReq |Calcul |Found  |Valid  |Submit |Accept ");
-        self.window.mv(y + 2, x + 78);
-        self.window.hline(ACS_HLINE(), 48);
+        let prefix = self.hash_prefix(w);
+        let unit = match self.stats_mode {
+            StatsMode::Rate => units::label(prefix, "/s"),
+            StatsMode::Efficiency => units::label(prefix, "/J"),
+            StatsMode::Count => units::label(prefix, ""),
+        };
+        for (offset, minute) in Screen::stat_blocks(compact).iter() {
+            let title = if *minute { "Last Minute" } else { "Since start" };
+            self.window.mvprintw(y, x + offset, format!("{} [{}]  ", title, unit));
+            self.window
+                .mvprintw(y + 1, x + offset, "WrkReq |Calcul |Found  |Valid  |Submit |Accept ");
+            self.window.mv(y + 2, x + offset);
+            self.window.hline(ACS_HLINE(), 48);
+        }
+        self.window.mvprintw(y, x + 51, "(% valid/found, rejected)");
 
-        // output totals
+        // output totals, the worksource and fee cover the whole rig
+        let fleet_power = metrics::fleet_power(&self.wd.workers);
         // worksource
-        self.draw_stat_line(y + 3, x, &w.worksource.stats);
+        self.draw_stat_line(y + 3, x, &w.worksource.stats, fleet_power, prefix, compact);
+        self.draw_effective_line(y + 4, x, &w.worksource, fleet_power, prefix, compact);
         // fee
-        self.draw_stat_line(y + 4, x, &w.fee.stats);
+        self.draw_stat_line(y + 5, x, &w.fee.stats, fleet_power, prefix, compact);
+        self.draw_effective_line(y + 6, x, &w.fee, fleet_power, prefix, compact);
         // total
-        self.draw_stat_line(y + 5, x, &core.stats);
+        self.draw_stat_line(y + 7, x, &core.stats, w.input_power, prefix, compact);
+        self.window.mvprintw(y + 8, x, format!("{:28}", "  valid, rejected"));
+        self.draw_ratios(y + 8, x, &core.stats, compact);
     }
 
-    fn draw_stat_line(&self, y: i32, x: i32, stats: &webdata::Stats) {
-        self.window.mvprintw(y, x, format!("{:29}", stats.name));
-        self.window.mvprintw(
-            y,
-            x + 28,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.requested)),
-        );
-        self.window.mvprintw(
-            y,
-            x + 36,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.calculated)),
-        );
-        self.window.mvprintw(
-            y,
-            x + 44,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.found)),
-        );
-        self.window.mvprintw(
-            y,
-            x + 52,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.valid)),
-        );
-        self.window.mvprintw(
-            y,
-            x + 60,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.submitted)),
-        );
-        self.window.mvprintw(
-            y,
-            x + 68,
-            Screen::float_to_string1(Screen::calc_total(stats.total, stats.total.accepted)),
-        );
+    //
+    // Valid over found under the Valid columns and the rejected share percentage
+    // under the Accept columns, colored by their configured thresholds.
+    //
+    fn draw_ratios(&self, y: i32, x: i32, stats: &webdata::Stats, compact: bool) {
+        for (offset, minute) in Screen::stat_blocks(compact).iter() {
+            let stat = if *minute { &stats.minute } else { &stats.total };
+            self.draw_percent(
+                y,
+                x + offset + 24,
+                metrics::valid_percent(stat),
+                &self.config.valid_percent,
+            );
+            self.draw_percent(
+                y,
+                x + offset + 40,
+                metrics::reject_percent(stat),
+                &self.config.reject_percent,
+            );
+        }
+    }
 
-        self.window
-            .mvprintw(y, x + 78, Screen::float_to_string1(stats.minute.requested / 60.0));
-        self.window
-            .mvprintw(y, x + 86, Screen::float_to_string1(stats.minute.calculated / 60.0));
-        self.window
-            .mvprintw(y, x + 94, Screen::float_to_string1(stats.minute.found / 60.0));
-        self.window
-            .mvprintw(y, x + 102, Screen::float_to_string1(stats.minute.valid / 60.0));
-        self.window
-            .mvprintw(y, x + 110, Screen::float_to_string1(stats.minute.submitted / 60.0));
-        self.window
-            .mvprintw(y, x + 118, Screen::float_to_string1(stats.minute.accepted / 60.0));
+    fn draw_percent(&self, y: i32, x: i32, value: Option<f32>, threshold: &config::Threshold) {
+        match value {
+            Some(value) => {
+                let attr = self.set_text_colors(threshold.health(value));
+                self.window.mvprintw(y, x, format!("{:>6.1}%", value));
+                self.window.attroff(attr);
+            }
+            None => {
+                self.window.mvprintw(y, x, format!("{:>7}", "-"));
+            }
+        }
+    }
+
+    //
+    // The pool side effective hashrate worked out from the accepted shares, under
+    // the device side calculated rate it should match, and the gap between them.
+    //
+    fn draw_effective_line(&self, y: i32, x: i32, algo: &webdata::Algo, power: f32, prefix: usize, compact: bool) {
+        let effective = metrics::effective_hashrate(algo);
+        let calculated = metrics::total_rate(&algo.stats.total, algo.stats.total.calculated);
+        self.window.mvprintw(y, x, format!("{:36}", "  pool effective"));
+        if let Some(gap) = metrics::hashrate_gap(effective.total, calculated) {
+            let health = if gap <= -EFFECTIVE_GAP_CRITICAL {
+                "critical"
+            } else if gap <= -EFFECTIVE_GAP_WARN {
+                "hold"
+            } else {
+                ""
+            };
+            let attr = self.set_text_colors(health);
+            self.window.mvprintw(y, x + 17, format!("{:>+7.1}%", gap));
+            self.window.attroff(attr);
+        }
+        let total_secs = metrics::span_secs(&algo.stats.total);
+        for (offset, minute) in Screen::stat_blocks(compact).iter() {
+            // under the Calcul column
+            let value = if *minute {
+                self.stat_value(effective.minute, effective.minute.map(|r| r * 60.0), power, prefix)
+            } else {
+                self.stat_value(effective.total, effective.total.map(|r| r * total_secs), power, prefix)
+            };
+            self.window.mvprintw(y, x + offset + 8, value);
+        }
+        self.draw_ratios(y, x, &algo.stats, compact);
+    }
+
+    //
+    // Below a zoomed stats table, the totals of every device on a row each, as
+    // many as there is room for.
+    //
+    fn draw_device_stats(&self, r: layout::Rect, prefix: usize, compact: bool) {
+        let y = r.y + 10;
+        let rows = (r.bottom() - y - 1).max(0) as usize;
+        if rows == 0 {
+            return;
+        }
+        self.window.mvprintw(y, r.x, format!("{:29}", "Devices"));
+        for (i, w) in self.wd.workers.iter().take(rows).enumerate() {
+            if let Some(core) = w.cores.cores.first() {
+                let row = y + 1 + i as i32;
+                self.draw_stat_line(row, r.x, &core.stats, w.input_power, prefix, compact);
+                // the device rather than the pool its core works for
+                self.window
+                    .mvprintw(row, r.x, format!("{:29.29}", format!("{:2} {}", i + 1, w.name)));
+            }
+        }
+    }
+
+    fn draw_stat_line(&self, y: i32, x: i32, stats: &webdata::Stats, power: f32, prefix: usize, compact: bool) {
+        self.window.mvprintw(y, x, format!("{:29}", stats.name));
+        for (offset, minute) in Screen::stat_blocks(compact).iter() {
+            let stat = if *minute { &stats.minute } else { &stats.total };
+            let counts = [
+                stat.requested,
+                stat.calculated,
+                stat.found,
+                stat.valid,
+                stat.submitted,
+                stat.accepted,
+            ];
+            for (column, count) in counts.iter().enumerate() {
+                let rate = if *minute {
+                    metrics::minute_rate(*count)
+                } else {
+                    metrics::total_rate(stat, *count)
+                };
+                self.window.mvprintw(
+                    y,
+                    x + offset + 8 * column as i32,
+                    self.stat_value(Some(rate), Some(*count), power, prefix),
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,76 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::history::{self, Metric};
+use crate::webdata;
+
+//
+// Minimum, maximum and mean of every value seen for a metric, kept without
+// holding on to the values themselves.
+//
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Running {
+    pub min: f64,
+    pub max: f64,
+    pub last: f64,
+    pub count: u64,
+    sum: f64,
+}
+
+impl Running {
+    pub fn new(value: f64) -> Running {
+        Running {
+            min: value,
+            max: value,
+            last: value,
+            count: 1,
+            sum: value,
+        }
+    }
+
+    pub fn record(&mut self, value: f64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.last = value;
+        self.count += 1;
+        self.sum += value;
+    }
+
+    pub fn avg(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+//
+// Session statistics of every metric of every device since viewerator started
+// or they were last reset, keyed by hw_uid like the history.
+//
+#[derive(Default)]
+pub struct Session {
+    devices: HashMap<String, BTreeMap<Metric, Running>>,
+}
+
+impl Session {
+    pub fn record_workers(&mut self, workers: &[webdata::Worker]) {
+        for w in workers {
+            let device = self.devices.entry(w.hw_uid.clone()).or_default();
+            for (metric, value) in history::samples(w) {
+                device
+                    .entry(metric)
+                    .and_modify(|r| r.record(value))
+                    .or_insert_with(|| Running::new(value));
+            }
+        }
+    }
+
+    pub fn get(&self, hw_uid: &str, metric: &Metric) -> Option<&Running> {
+        self.devices.get(hw_uid)?.get(metric)
+    }
+
+    pub fn device(&self, hw_uid: &str) -> Option<&BTreeMap<Metric, Running>> {
+        self.devices.get(hw_uid)
+    }
+
+    pub fn reset(&mut self) {
+        self.devices.clear();
+    }
+}
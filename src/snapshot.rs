@@ -0,0 +1,56 @@
+use log::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+//
+// The last status response successfully parsed for a host, kept verbatim in the
+// config directory so the next launch has something to show straight away.  The
+// file's modification time records when it was fetched, and since it is a plain
+// minerator response it can also be fed back in with --input_file.
+//
+pub struct Snapshot {
+    pub response: String,
+    pub saved: SystemTime,
+}
+
+pub fn path(config_dir: &str, host: &str) -> PathBuf {
+    host_file(config_dir, "snapshot", host)
+}
+
+//
+// A file of the given kind in the config directory that belongs to one host,
+// named after it with anything unsafe in a file name replaced.
+//
+pub fn host_file(config_dir: &str, kind: &str, host: &str) -> PathBuf {
+    let name: String = host
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    Path::new(config_dir).join(format!("{}-{}.json", kind, name))
+}
+
+impl Snapshot {
+    pub fn load(config_dir: &str, host: &str) -> Option<Snapshot> {
+        let path = path(config_dir, host);
+        let response = fs::read_to_string(&path).ok()?;
+        let saved = fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+        debug!("Loaded snapshot {}", path.display());
+        Some(Snapshot { response, saved })
+    }
+
+    pub fn save(config_dir: &str, host: &str, response: &str) -> io::Result<()> {
+        fs::write(path(config_dir, host), response)
+    }
+}
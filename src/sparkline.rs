@@ -0,0 +1,62 @@
+use std::time::SystemTime;
+
+use crate::history::Sample;
+
+const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+//
+// Averages the samples falling in each of `width` equal slices of time between
+// start and end.  Slices without samples are None so gaps show as gaps.
+//
+pub fn bucket(samples: &[Sample], start: SystemTime, end: SystemTime, width: usize) -> Vec<Option<f64>> {
+    let mut sums = vec![0.0; width];
+    let mut counts = vec![0; width];
+    let span = match end.duration_since(start) {
+        Ok(span) if width > 0 && span.as_secs_f64() > 0.0 => span.as_secs_f64(),
+        _ => return vec![None; width],
+    };
+    for s in samples {
+        let offset = match s.time.duration_since(start) {
+            Ok(offset) => offset.as_secs_f64(),
+            Err(_) => continue,
+        };
+        let slot = (((offset / span) * width as f64) as usize).min(width - 1);
+        sums[slot] += s.value;
+        counts[slot] += 1;
+    }
+    sums.iter()
+        .zip(counts.iter())
+        .map(|(sum, count)| {
+            if *count > 0 {
+                Some(sum / f64::from(*count))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+//
+// Draws the values as block characters scaled between their own min and max.
+// A flat line sits on the bottom row.
+//
+pub fn render(values: &[Option<f64>]) -> String {
+    let present = values.iter().filter_map(|v| *v);
+    let min = present.clone().fold(f64::INFINITY, f64::min);
+    let max = present.fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| match v {
+            Some(v) if max > min => {
+                let level = ((v - min) / (max - min) * (BARS.len() - 1) as f64).round() as usize;
+                BARS[level.min(BARS.len() - 1)]
+            }
+            Some(_) => BARS[0],
+            None => ' ',
+        })
+        .collect()
+}
+
+pub fn sparkline(samples: &[Sample], start: SystemTime, end: SystemTime, width: usize) -> String {
+    render(&bucket(samples, start, end, width))
+}
@@ -0,0 +1,218 @@
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::health;
+
+//
+// Foreground and background of one health level, each a curses color name
+// (black, red, green, yellow, blue, magenta, cyan, white) or a 0-255 index.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Colors {
+    pub fg: String,
+    pub bg: String,
+}
+
+impl Colors {
+    pub fn new(fg: &str, bg: &str) -> Colors {
+        Colors {
+            fg: fg.to_string(),
+            bg: bg.to_string(),
+        }
+    }
+}
+
+//
+// The colors of each health level by minerator's name for it.
+//
+pub type Palette = BTreeMap<String, Colors>;
+
+//
+// How the screen is colored, as the config file has it, like
+//   "theme": { "name": "colorblind", "gradient": true }
+// Named themes in themes can replace or add to the built in ones, and any
+// level they leave out keeps its default colors.
+//
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub name: String,
+    pub monochrome: bool,
+    // temperatures shaded from cold to hot on terminals with 256 colors or more
+    pub gradient: bool,
+    pub cold: f32,
+    pub hot: f32,
+    pub themes: BTreeMap<String, Palette>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            name: "default".to_string(),
+            monochrome: false,
+            gradient: true,
+            cold: 40.0,
+            hot: 90.0,
+            themes: BTreeMap::new(),
+        }
+    }
+}
+
+// the names built_in knows, listed when the configured theme isn't one of them
+pub const BUILT_IN: [&str; 2] = ["default", "colorblind"];
+
+//
+// The built in palettes.  colorblind keeps every level apart by brightness
+// as well as hue, so red and magenta are never the only difference.
+//
+pub fn built_in(name: &str) -> Option<Palette> {
+    let levels: [(&str, &str); 5] = match name {
+        "default" => [
+            ("black", "green"),
+            ("black", "cyan"),
+            ("black", "yellow"),
+            ("white", "magenta"),
+            ("white", "red"),
+        ],
+        "colorblind" => [
+            ("white", "blue"),
+            ("black", "cyan"),
+            ("black", "white"),
+            ("black", "yellow"),
+            ("white", "red"),
+        ],
+        _ => return None,
+    };
+    // LEVELS runs worst first, the tables above best first
+    Some(
+        health::LEVELS
+            .iter()
+            .rev()
+            .zip(levels.iter())
+            .map(|(level, (fg, bg))| (level.to_string(), Colors::new(fg, bg)))
+            .collect(),
+    )
+}
+
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+//
+// A curses color number from a name or index, checked against how many
+// colors the terminal has.
+//
+pub fn parse_color(name: &str, colors: i32) -> Option<i16> {
+    let name = name.trim().to_lowercase();
+    let color = match COLOR_NAMES.iter().position(|c| *c == name) {
+        Some(i) => i as i16,
+        None => name.parse().ok()?,
+    };
+    if color >= 0 && i32::from(color) < colors.max(8) {
+        Some(color)
+    } else {
+        None
+    }
+}
+
+//
+// The foreground and background color numbers of each level, worst first in
+// the order of LEVELS.  Whatever the settings get wrong, or the terminal
+// can't show, is logged and left at the default.
+//
+pub fn resolve(settings: &Settings, colors: i32) -> Vec<(i16, i16)> {
+    let default = built_in("default").unwrap_or_default();
+    let palette = match settings.themes.get(&settings.name).cloned() {
+        Some(palette) => palette,
+        None => built_in(&settings.name).unwrap_or_else(|| {
+            let mut names: Vec<&str> = BUILT_IN.to_vec();
+            names.extend(settings.themes.keys().map(String::as_str));
+            warn!(
+                "Unknown theme {}, using default (themes are {})",
+                settings.name,
+                names.join(", ")
+            );
+            default.clone()
+        }),
+    };
+    health::LEVELS
+        .iter()
+        .map(|level| {
+            let parse =
+                |colors_of: &Colors| Some((parse_color(&colors_of.fg, colors)?, parse_color(&colors_of.bg, colors)?));
+            palette
+                .get(*level)
+                .and_then(|c| {
+                    let pair = parse(c);
+                    if pair.is_none() {
+                        warn!("Unusable colors {:?} for {} in theme {}", c, level, settings.name);
+                    }
+                    pair
+                })
+                .or_else(|| default.get(*level).and_then(parse))
+                .unwrap_or((7, 0))
+        })
+        .collect()
+}
+
+//
+// Monochrome when asked for, or when NO_COLOR is set to anything
+// (https://no-color.org).
+//
+pub fn monochrome(settings: &Settings, no_color: Option<&str>) -> bool {
+    settings.monochrome || matches!(no_color, Some(v) if !v.is_empty())
+}
+
+//
+// Attributes standing in for the health colors in monochrome, boldest for the
+// worst.
+//
+pub fn attributes(level: &str) -> pancurses::chtype {
+    match level {
+        "critical" => pancurses::A_REVERSE | pancurses::A_BOLD,
+        "slowDecrease" => pancurses::A_REVERSE,
+        "hold" => pancurses::A_BOLD | pancurses::A_UNDERLINE,
+        "slowIncrease" => pancurses::A_UNDERLINE,
+        _ => pancurses::A_NORMAL,
+    }
+}
+
+//
+// Marks shown next to values when there's no color to carry the health, only
+// for the levels that need a look.
+//
+pub fn marker(level: &str) -> &'static str {
+    match level {
+        "critical" => "!!",
+        "slowDecrease" => "!",
+        "hold" => "=",
+        _ => "",
+    }
+}
+
+// blue through cyan, green and yellow to red in the xterm 256 color cube
+pub const GRADIENT_256: [i16; 16] = [21, 27, 33, 39, 45, 51, 50, 49, 48, 46, 82, 118, 190, 220, 208, 196];
+
+//
+// Which of steps shades a temperature falls in between cold and hot.
+//
+pub fn gradient_step(value: f32, cold: f32, hot: f32, steps: usize) -> usize {
+    if steps == 0 || !value.is_finite() || hot <= cold {
+        return 0;
+    }
+    let fraction = ((value - cold) / (hot - cold)).clamp(0.0, 1.0);
+    ((fraction * steps as f32) as usize).min(steps - 1)
+}
+
+//
+// A shade of the gradient from blue at 0 to red at 1 in curses' 0-1000 color
+// scale, for terminals that can redefine their colors.
+//
+pub fn gradient_rgb(fraction: f32) -> (i16, i16, i16) {
+    let f = fraction.clamp(0.0, 1.0);
+    let (r, g, b) = if f < 0.5 {
+        (0.0, f * 2.0, 1.0 - f * 2.0)
+    } else {
+        ((f - 0.5) * 2.0, 1.0 - (f - 0.5) * 2.0, 0.0)
+    };
+    ((r * 1000.0) as i16, (g * 1000.0) as i16, (b * 1000.0) as i16)
+}
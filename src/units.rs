@@ -0,0 +1,82 @@
+use crate::metrics;
+
+pub const PREFIXES: [&str; 7] = ["", "K", "M", "G", "T", "P", "E"];
+
+//
+// Index into PREFIXES for a unit name like "GH", "gh/s" or "TH/J".
+//
+pub fn parse(unit: &str) -> Option<usize> {
+    let unit = unit.trim().to_uppercase();
+    let prefix = unit.split('/').next()?.strip_suffix('H')?;
+    PREFIXES.iter().position(|p| *p == prefix)
+}
+
+//
+// The largest prefix that keeps a number of hashes at or above one.
+//
+pub fn auto(hashes: f64) -> usize {
+    if !hashes.is_finite() || hashes < 1000.0 {
+        return 0;
+    }
+    ((hashes.log10() / 3.0).floor() as usize).min(PREFIXES.len() - 1)
+}
+
+pub fn label(prefix: usize, per: &str) -> String {
+    format!("{}H{}", PREFIXES[prefix.min(PREFIXES.len() - 1)], per)
+}
+
+//
+// A number from the minerator stats, which count in millions of hashes, in the
+// units of prefix.
+//
+pub fn scale_stat(value: f32, prefix: usize) -> f32 {
+    let hashes = f64::from(value) * metrics::HASHES_PER_STAT_UNIT;
+    (hashes / 1000f64.powi(prefix.min(PREFIXES.len() - 1) as i32)) as f32
+}
+
+//
+// Seven characters for any number, adding a K..E suffix as it grows and
+// falling back to scientific notation beyond that.
+//
+pub fn compact(f: f32) -> String {
+    if !f.is_finite() {
+        return format!("{:>7}", "-");
+    }
+    let mut value = f;
+    let mut suffix = 0;
+    while value.abs() >= 1000.0 && suffix < PREFIXES.len() - 1 {
+        value /= 1000.0;
+        suffix += 1;
+    }
+    if value.abs() >= 1000.0 {
+        format!("{:>7.0e}", f)
+    } else if suffix == 0 {
+        format!("{:>7.1}", value)
+    } else {
+        format!("{:>6.1}{}", value, PREFIXES[suffix])
+    }
+}
+
+//
+// Five characters for a session min or max: three significant figures with a
+// K..E suffix once past a thousand.
+//
+pub fn short(f: f64) -> String {
+    if !f.is_finite() {
+        return "-".to_string();
+    }
+    let mut value = f;
+    let mut suffix = 0;
+    while value.abs() >= 999.5 && suffix < PREFIXES.len() - 1 {
+        value /= 1000.0;
+        suffix += 1;
+    }
+    let prefix = PREFIXES[suffix];
+    if value.abs() >= 99.5 {
+        format!("{:.0}{}", value, prefix)
+    } else if value.abs() >= 9.95 || suffix > 0 {
+        format!("{:.1}{}", value, prefix)
+    } else {
+        format!("{:.2}", value)
+    }
+}
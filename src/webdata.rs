@@ -3,10 +3,19 @@ use serde::Deserialize;
 use std::error::Error;
 use std::fs;
 use std::process::{exit, ExitCode, Termination};
+use std::time::{Duration, SystemTime};
+
+use crate::energy;
+use crate::events;
+use crate::health;
+use crate::history;
+use crate::session;
+use crate::snapshot;
 
 pub struct Worker {
     pub name: String,
     pub dna: String,
+    pub hw_uid: String,
     pub hw_type: u16,
     pub worse_health: String,
     pub input_power: f32,
@@ -20,6 +29,9 @@ pub struct Worker {
     pub pex_12v: f32,
     pub pex_12v_health: String,
     pub vccint: f32,
+    // what the regulator reports it is putting out, and what it was asked for
+    pub voltage: f32,
+    pub vccint_setpoint: Option<f32>,
     pub vccint_current: f32,
     pub vccint_current_health: String,
     pub vrctrl_temp: f32,
@@ -98,6 +110,8 @@ pub struct Stats {
 
 #[derive(Deserialize, Debug)]
 pub struct Core {
+    #[serde(default)]
+    pub algo: String,
     pub clock: Clock,
     pub stats: Stats,
 }
@@ -110,8 +124,12 @@ pub struct Cores {
 #[derive(Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
 pub struct Algo {
+    #[serde(default)]
+    pub algo: String,
     pub difficulty: f64,
-    //    pub hashesPerDiff1: f64,
+    // older minerators fold this into the difficulty
+    #[serde(default = "Algo::default_hashes_per_diff1")]
+    pub hashesPerDiff1: f64,
     pub stats: Stats,
 }
 
@@ -122,10 +140,19 @@ impl Default for Algo {
 }
 
 impl Algo {
+    fn default_hashes_per_diff1() -> f64 {
+        1.0
+    }
+
+    pub fn hashes_per_share(&self) -> f64 {
+        self.difficulty * self.hashesPerDiff1
+    }
+
     pub fn new() -> Algo {
         Algo {
+            algo: String::new(),
             difficulty: 0.0,
-            //            hashesPerDiff1: 0.0,
+            hashesPerDiff1: Algo::default_hashes_per_diff1(),
             stats: Stats {
                 minute: StatDetail::new(),
                 name: "None".to_string(),
@@ -137,10 +164,30 @@ impl Algo {
 
 pub struct WebData {
     client: reqwest::blocking::Client,
+    snapshot_dir: Option<String>,
+    // the last response polled and when a snapshot of one was last written
+    latest_response: Option<String>,
+    snapshot_saved: Option<SystemTime>,
+    // the minerator polled, from --host or else the first in the config's fleet
+    pub host: String,
     pub minerator: String,
     pub workers: Vec<Worker>,
+    pub updated: Option<SystemTime>,
+    pub stale: bool,
+    pub history: history::History,
+    pub health: health::Tracker,
+    pub session: session::Session,
+    pub events: events::Events,
+    // only kept when watching a live minerator, never for replayed files
+    pub energy: Option<energy::Energy>,
 }
 
+pub const DEFAULT_HOST: &str = "http://localhost";
+const SNAPSHOT_INTERVAL_SECS: u64 = 60;
+// readings further apart than this mean viewerator wasn't watching, so the
+// energy and health residency fed from mark_fresh leave the gap out
+pub const MAX_GAP: Duration = Duration::from_secs(120);
+
 pub const HWTYPE_BCU: u16 = 1;
 pub const HWTYPE_CVP: u16 = 2;
 
@@ -154,23 +201,88 @@ impl WebData {
     pub fn new() -> WebData {
         WebData {
             client: reqwest::blocking::Client::new(),
+            snapshot_dir: None,
+            latest_response: None,
+            snapshot_saved: None,
+            host: DEFAULT_HOST.to_string(),
             minerator: "None".to_string(),
             workers: vec![],
+            updated: None,
+            stale: false,
+            history: history::History::default(),
+            health: health::Tracker::default(),
+            session: session::Session::default(),
+            events: events::Events::default(),
+            energy: None,
+        }
+    }
+
+    pub fn with_timeout(timeout: Duration) -> WebData {
+        WebData {
+            client: reqwest::blocking::Client::builder().timeout(timeout).build().unwrap(),
+            ..WebData::new()
         }
     }
 
+    pub fn fetch_status(&self, host: &str) -> reqwest::Result<reqwest::blocking::Response> {
+        let url = format!("{}/api/status", host);
+        debug!("Looking at url: {}", url);
+        self.client.get(&url).send()
+    }
+
+    //
+    // Checks whether minerator answers on host, returning its version and
+    // device count.  Unlike getdata, failures are only logged, never fatal.
+    //
+    pub fn probe(&self, host: &str) -> Option<(String, usize)> {
+        let resp = match self.fetch_status(host) {
+            Ok(resp) => resp,
+            Err(e) => {
+                debug!("No answer from {}: {}", host, e);
+                return None;
+            }
+        };
+        if !resp.status().is_success() {
+            debug!("{} answered with {}", host, resp.status());
+            return None;
+        }
+        match resp.text() {
+            Ok(response) => WebData::status_summary(&response),
+            Err(e) => {
+                debug!("Problem reading response from {}: {}", host, e);
+                None
+            }
+        }
+    }
+
+    //
+    // Pulls the minerator version and number of devices out of a status
+    // response, or None if it doesn't look like one.
+    //
+    pub fn status_summary(response: &str) -> Option<(String, usize)> {
+        let blob: serde_json::Value = serde_json::from_str(response).ok()?;
+        let minerator = blob["minerator"].as_str()?.to_string();
+        let mut devices = 0;
+        if let serde_json::Value::Object(config) = &blob["workers"] {
+            for (_name, device) in config.iter() {
+                if let serde_json::Value::Array(workers) = &device["devices"] {
+                    devices += workers.len();
+                }
+            }
+        }
+        Some((minerator, devices))
+    }
+
     pub fn getdata<'a>(&mut self, matches: &clap::ArgMatches<'a>) {
         let input_file = matches.value_of("input_file").unwrap_or("");
         if input_file != "" {
             let input = fs::read_to_string(input_file).unwrap();
-            self.process_response(input);
+            if self.process_response(input) {
+                self.mark_fresh();
+            }
         } else {
-            let default_host = "http://localhost";
-            let host = matches.value_of("host").unwrap_or(&default_host);
-            let url = format!("{}/api/status", host);
-
-            debug!("Looking at url: {}", url);
-            let send = self.client.get(&url).send();
+            let host = self.host.clone();
+            let send = self.fetch_status(&host);
             let resp = match send {
                 Err(e) => {
                     if e.is_timeout() {
@@ -178,21 +290,19 @@ impl WebData {
                             "Connection to minerator timed out. Please ensure minerator is running\n{:?}",
                             e
                         );
-                        pancurses::endwin();
-                        eprintln!(
+                        self.connection_failed(&format!(
                             "Connection to minerator timed out. Please ensure minerator is running\n{:?}",
                             e
-                        );
-                        exit(ExitCode::FAILURE.report())
+                        ));
+                        return;
                     }
                     if e.is_redirect() {
                         info!("server redirecting too many times or making loop\n{:?}", e);
-                        pancurses::endwin();
-                        eprintln!(
+                        self.connection_failed(&format!(
                             "Connection to server redirecting too many times or making loop\n{:?}",
                             e
-                        );
-                        exit(ExitCode::FAILURE.report())
+                        ));
+                        return;
                     }
                     match e.status() {
                         Some(err) => info!("Problem parsing info {}", err),
@@ -202,24 +312,31 @@ impl WebData {
                             if err1.source().unwrap().to_string().contains("Connection refused") {
                                 info!("TCP Connect error to minerator. Please ensure minerator is running");
                                 debug!("TCP Connect error is {:?}", err1);
-                                pancurses::endwin();
-                                eprintln!("TCP Connect error to minerator. Please ensure minerator is running");
-                                exit(ExitCode::FAILURE.report())
+                                self.connection_failed(
+                                    "TCP Connect error to minerator. Please ensure minerator is running",
+                                );
+                                return;
                             }
                         }
                     }
+                    self.stale = true;
                     return;
                 }
                 Ok(resp) => resp,
             };
             if resp.status().is_server_error() {
                 info!("Server error");
+                self.stale = true;
                 return;
             }
 
             match resp.text() {
                 Ok(response) => {
-                    self.process_response(response);
+                    if self.process_response(response.clone()) {
+                        self.mark_fresh();
+                        self.latest_response = Some(response);
+                        self.save_snapshot_if_due(SystemTime::now());
+                    }
                 }
                 Err(e) => {
                     match e.status() {
@@ -229,11 +346,96 @@ impl WebData {
                     if e.is_redirect() {
                         info!("server redirecting too many times or making loop");
                     }
+                    self.stale = true;
                 }
             }
         }
     }
 
+    //
+    // Shows the last snapshot saved for host until fresh data arrives, and
+    // remembers where to save new ones.  Returns whether there was one to show.
+    //
+    pub fn load_snapshot(&mut self, config_dir: &str, host: &str) -> bool {
+        self.snapshot_dir = Some(config_dir.to_string());
+        let snap = match snapshot::Snapshot::load(config_dir, host) {
+            Some(snap) => snap,
+            None => return false,
+        };
+        if !self.process_response(snap.response) {
+            return false;
+        }
+        info!(
+            "Showing snapshot of {} from {:?} until minerator answers",
+            host, snap.saved
+        );
+        self.updated = Some(snap.saved);
+        self.stale = true;
+        true
+    }
+
+    //
+    // Writes the last response polled as the host's snapshot, if it hasn't been
+    // already.
+    //
+    pub fn save_snapshot(&mut self) {
+        if let (Some(config_dir), Some(response)) = (&self.snapshot_dir, self.latest_response.take()) {
+            if let Err(err) = snapshot::Snapshot::save(config_dir, &self.host, &response) {
+                warn!("Unable to save snapshot: {}", err);
+            }
+            self.snapshot_saved = Some(SystemTime::now());
+        }
+    }
+
+    //
+    // Saves at most once a minute like the energy totals, the snapshot only needs
+    // to be recent enough to show something on the next launch.
+    //
+    fn save_snapshot_if_due(&mut self, now: SystemTime) {
+        let due = match self.snapshot_saved {
+            Some(saved) => now.duration_since(saved).unwrap_or_default() >= Duration::from_secs(SNAPSHOT_INTERVAL_SECS),
+            None => true,
+        };
+        if due {
+            self.save_snapshot();
+        }
+    }
+
+    //
+    // How old the data being shown is, if any has been shown at all.
+    //
+    pub fn age(&self) -> Option<Duration> {
+        self.updated
+            .map(|t| SystemTime::now().duration_since(t).unwrap_or_default())
+    }
+
+    fn mark_fresh(&mut self) {
+        let now = SystemTime::now();
+        self.updated = Some(now);
+        self.stale = false;
+        self.history.record_workers(now, &self.workers);
+        self.health.record_workers(now, &self.workers);
+        self.session.record_workers(&self.workers);
+        self.events.record_workers(now, &self.workers);
+        if let Some(energy) = &mut self.energy {
+            energy.record(now, &self.workers);
+            energy.save_if_due(now);
+        }
+    }
+
+    //
+    // Minerator can't be reached.  With nothing to show there is no point going
+    // on, otherwise keep showing what we have, marked as stale.
+    //
+    fn connection_failed(&mut self, msg: &str) {
+        if self.workers.is_empty() {
+            pancurses::endwin();
+            eprintln!("{}", msg);
+            exit(ExitCode::FAILURE.report())
+        }
+        self.stale = true;
+    }
+
     fn min_health(s1: &str, s2: &str) -> String {
         if s1 == "critical" || s2 == "critical" {
             String::from("critical")
@@ -248,12 +450,12 @@ impl WebData {
         }
     }
 
-    pub fn process_response(&mut self, response: String) {
+    pub fn process_response(&mut self, response: String) -> bool {
         let blob: serde_json::Value = match serde_json::from_str(&response) {
             Ok(blob) => blob,
             Err(err) => {
                 warn!("Error parsing json: {}", err);
-                return;
+                return false;
             }
         };
         self.workers.clear();
@@ -332,6 +534,9 @@ impl WebData {
                         let pex_12v: f32 = serde_json::from_str(&*pv).unwrap();
                         let vi = w["bmc"]["adc"]["vccint"].to_string();
                         let vccint: f32 = serde_json::from_str(&*vi).unwrap();
+                        let voltage = w["voltage"].as_f64().unwrap_or(0.0) as f32;
+                        // not every board reports a setpoint
+                        let vccint_setpoint = w["bmc"]["vccintSetpoint"].as_f64().map(|v| v as f32);
                         let vc = w["bmc"]["adc"]["vccintCurrent"].to_string();
                         let vccint_current: f32 = serde_json::from_str(&*vc).unwrap();
                         let mut vrctrl_temp = 0.0;
@@ -369,6 +574,8 @@ impl WebData {
                         if dna == "" {
                             dna = w["hwUID"].as_str().unwrap();
                         }
+                        // older minerators only report the dna
+                        let hw_uid = w["hwUID"].as_str().unwrap_or(dna);
                         let input_power_health = w["bmc"]["health"]["inputPower"].as_str().unwrap().to_string();
                         worse_health = WebData::min_health(&worse_health, &input_power_health);
                         let aux_current_health = w["bmc"]["health"]["inputCurrentAUX"].as_str().unwrap().to_string();
@@ -386,6 +593,7 @@ impl WebData {
                         let phase1_temperature_health = phase0_temperature_health.clone();
                         self.workers.push(Worker {
                             dna: dna.to_string(),
+                            hw_uid: hw_uid.to_string(),
                             name: w["name"].as_str().unwrap().to_string(),
                             hw_type,
                             worse_health,
@@ -400,6 +608,8 @@ impl WebData {
                             pex_12v,
                             pex_12v_health,
                             vccint,
+                            voltage,
+                            vccint_setpoint,
                             vccint_current,
                             vccint_current_health,
                             vrctrl_temp,
@@ -423,5 +633,6 @@ impl WebData {
         }
 
         //        debug!("Read first device dna {}, name {}", self.workers[0].dna, self.workers[0].name);
+        true
     }
 }
@@ -0,0 +1,32 @@
+use mylib::chart;
+
+#[test]
+fn summarize_values() {
+    let summary = chart::summarize(&[2.0, 4.0, 9.0]).unwrap();
+    assert_eq!(summary.min, 2.0);
+    assert_eq!(summary.max, 9.0);
+    assert_eq!(summary.avg, 5.0);
+    assert!(chart::summarize(&[]).is_none());
+}
+
+#[test]
+fn combine_sums_or_averages() {
+    let a = vec![Some(1.0), None, Some(3.0)];
+    let b = vec![Some(3.0), None, None];
+    assert_eq!(
+        chart::combine(&[a.clone(), b.clone()], true),
+        vec![Some(4.0), None, Some(3.0)]
+    );
+    assert_eq!(chart::combine(&[a, b], false), vec![Some(2.0), None, Some(3.0)]);
+}
+
+#[test]
+fn plot_rising_line() {
+    let mut canvas = chart::Canvas::new(2, 1);
+    canvas.plot(0, &[Some(0.0), Some(1.0), Some(2.0), Some(3.0)], 0.0, 3.0);
+    let rows = canvas.rows();
+    // bottom left dot in the first column, rising to the top right
+    assert_eq!(rows[0][0].ch, '⡠');
+    assert_eq!(rows[0][1].ch, '⠊');
+    assert_eq!(rows[0][1].series, Some(0));
+}
@@ -0,0 +1,11 @@
+use mylib::*;
+use std::fs;
+
+//
+// The two BCU and one CVP rig most tests run against, parsed as if polled.
+//
+pub fn rig() -> webdata::WebData {
+    let mut wd = webdata::WebData::new();
+    wd.process_response(fs::read_to_string("tests/data/2bcu1cvp.json").unwrap());
+    wd
+}
@@ -0,0 +1,35 @@
+use mylib::layout::Panel;
+use mylib::*;
+use std::env;
+use std::fs;
+
+#[test]
+fn layout_saves_back() {
+    let dir = env::temp_dir().join("viewerator-config-test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+
+    // no file yet, so the first change creates it
+    let mut config = config::Config::load(dir);
+    config.layout.toggle(Panel::Energy);
+    config.save_back().unwrap();
+    let config = config::Config::load(dir);
+    assert_eq!(config.layout.hidden, vec![Panel::Energy]);
+}
+
+#[test]
+fn malformed_file_is_left_alone() {
+    let dir = env::temp_dir().join("viewerator-config-malformed-test");
+    fs::create_dir_all(&dir).unwrap();
+    let path = config::Config::path(dir.to_str().unwrap());
+    let input = "{ \"hosts\": [\"http://rig1\"], \"mouse\": fals }";
+    fs::write(&path, input).unwrap();
+
+    let mut config = config::Config::load(dir.to_str().unwrap());
+    assert!(config.hosts.is_empty());
+    assert!(!config.writable());
+    config.layout.toggle(Panel::Log);
+    assert!(config.save_back().is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), input);
+}
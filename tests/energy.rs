@@ -0,0 +1,79 @@
+mod common;
+
+use mylib::*;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn integrates_power_per_day() {
+    let workers = common::rig().workers;
+    let mut energy = energy::Energy::default();
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    energy.record_on("2020-09-13", start, &workers);
+    energy.record_on("2020-09-13", start + Duration::from_secs(60), &workers);
+    // too long a gap to know what happened in between
+    energy.record_on("2020-09-14", start + Duration::from_secs(3600), &workers);
+    energy.record_on("2020-09-14", start + Duration::from_secs(3660), &workers);
+
+    let w = &workers[0];
+    let kwh = f64::from(w.input_power) * 60.0 / 3_600_000.0;
+    assert!((energy.today(&w.hw_uid, "2020-09-13") - kwh).abs() < 1e-9);
+    assert!((energy.total(&w.hw_uid) - 2.0 * kwh).abs() < 1e-9);
+    let power: f64 = workers.iter().map(|w| f64::from(w.input_power)).sum();
+    assert!((energy.rig_today("2020-09-14") - power * 60.0 / 3_600_000.0).abs() < 1e-9);
+    assert_eq!(energy.since, "2020-09-13");
+
+    let csv = energy.to_csv();
+    assert!(csv.starts_with("date,device,name,kwh\n"));
+    assert!(csv.contains("2020-09-14,rig,,"));
+}
+
+#[test]
+fn malformed_file_is_left_alone() {
+    let dir = env::temp_dir().join("viewerator-energy-malformed-test");
+    fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+    let path = energy::path(dir, "http://rig1");
+    let input = "{ \"since\": \"2020-09-13\", \"devices\": { ";
+    fs::write(&path, input).unwrap();
+
+    let mut energy = energy::Energy::load(dir, "http://rig1");
+    assert!(!energy.writable());
+    let workers = common::rig().workers;
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    energy.record_on("2020-09-13", start, &workers);
+    energy.record_on("2020-09-13", start + Duration::from_secs(60), &workers);
+    energy.save().unwrap();
+    energy.save_if_due(start + Duration::from_secs(60));
+    assert_eq!(fs::read_to_string(&path).unwrap(), input);
+}
+
+#[test]
+fn totals_are_per_host() {
+    let dir = env::temp_dir().join("viewerator-energy-host-test");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+    assert_eq!(
+        energy::path(dir, "http://rig2:8080"),
+        Path::new(dir).join("energy-rig2_8080.json")
+    );
+
+    // totals from before they were split go to the first host watched
+    let legacy = Path::new(dir).join(energy::ENERGY_FILE);
+    fs::write(&legacy, "{ \"since\": \"2020-09-13\" }").unwrap();
+    assert_eq!(energy::Energy::load(dir, "http://rig1").since, "2020-09-13");
+    assert!(!legacy.exists());
+
+    let workers = common::rig().workers;
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+    let mut rig2 = energy::Energy::load(dir, "http://rig2:8080");
+    assert!(rig2.since.is_empty());
+    rig2.record_on("2020-09-14", start, &workers);
+    rig2.record_on("2020-09-14", start + Duration::from_secs(60), &workers);
+    rig2.save().unwrap();
+    assert!(energy::Energy::load(dir, "http://rig2:8080").rig_today("2020-09-14") > 0.0);
+    assert_eq!(energy::Energy::load(dir, "http://rig1").rig_today("2020-09-14"), 0.0);
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use mylib::*;
+use std::time::SystemTime;
+
+#[test]
+fn health_changes_become_events() {
+    let mut events = events::Events::default();
+    let mut workers = common::rig().workers;
+    let now = SystemTime::now();
+    events.record_workers(now, &workers);
+    assert!(events.events().is_empty());
+
+    workers[0].worse_health = "critical".to_string();
+    workers[0].input_power_health = "critical".to_string();
+    events.record_workers(now, &workers);
+    let messages: Vec<&str> = events.events().iter().map(|e| e.message.as_str()).collect();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("worst health"));
+    assert!(messages[1].starts_with("Input Power"));
+    assert_eq!(events.events()[1].level, "critical");
+}
+
+#[test]
+fn devices_and_pools() {
+    let mut events = events::Events::new(2);
+    let mut workers = common::rig().workers;
+    let now = SystemTime::now();
+    events.record_workers(now, &workers);
+    let gone = workers.pop().unwrap();
+    for w in workers.iter_mut() {
+        w.worksource.stats.name = "backup:3333".to_string();
+    }
+    events.record_workers(now, &workers);
+    let recorded = events.events();
+    assert_eq!(recorded.len(), 2);
+    assert_eq!(recorded[0].device, gone.name);
+    assert_eq!(recorded[0].message, "disappeared");
+    assert!(recorded[1].message.ends_with("to backup:3333"));
+}
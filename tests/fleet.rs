@@ -0,0 +1,60 @@
+mod common;
+
+use mylib::fleet::Column;
+use mylib::*;
+
+#[test]
+fn row_per_device() {
+    let workers = common::rig().workers;
+    let rows = fleet::rows(&workers, Column::Index, false);
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows.iter().map(|r| r.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(rows[0].board, "BCU1525");
+    assert_eq!(rows[2].board, "CVP13");
+    let hottest = workers[0]
+        .sysmons
+        .sysmon
+        .iter()
+        .map(|s| s.temperature)
+        .fold(f32::MIN, f32::max);
+    assert_eq!(rows[0].hottest, Some(hottest));
+}
+
+#[test]
+fn sorts_on_any_column() {
+    let mut workers = common::rig().workers;
+    workers[1].sysmons.sysmon[0].temperature = 99.0;
+    workers[2].worse_health = "critical".to_string();
+    workers[0].input_power = f32::NAN;
+
+    let rows = fleet::rows(&workers, Column::Hottest, true);
+    assert_eq!(rows[0].index, 1);
+    let rows = fleet::rows(&workers, Column::Health, true);
+    assert_eq!(rows[0].index, 2);
+    // unreadable values go last whichever way it is sorted
+    let rows = fleet::rows(&workers, Column::Power, true);
+    assert_eq!(rows[2].index, 0);
+    let rows = fleet::rows(&workers, Column::Index, true);
+    assert_eq!(rows[0].index, 2);
+
+    assert_eq!(Column::BadNonce.next(), Column::Index);
+    assert_eq!(Column::Index.previous(), Column::BadNonce);
+}
+
+#[test]
+fn jump_and_device_bar() {
+    let workers = common::rig().workers;
+    assert_eq!(fleet::find(&workers, "2"), Some(1));
+    assert_eq!(fleet::find(&workers, "9"), None);
+    assert_eq!(fleet::find(&workers, ""), None);
+    let name = workers[2].name.to_uppercase();
+    assert_eq!(fleet::find(&workers, &name), Some(2));
+    let hw_uid = &workers[1].hw_uid[..12];
+    assert_eq!(fleet::find(&workers, hw_uid), Some(1));
+
+    assert_eq!(fleet::visible(3, 2, 9), 0..3);
+    assert_eq!(fleet::visible(20, 0, 8), 0..8);
+    assert_eq!(fleet::visible(20, 10, 8), 6..14);
+    assert_eq!(fleet::visible(20, 19, 8), 12..20);
+    assert_eq!(fleet::visible(20, 5, 0), 5..6);
+}
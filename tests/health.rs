@@ -0,0 +1,48 @@
+mod common;
+
+use mylib::*;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn residency_and_transitions() {
+    let start = SystemTime::UNIX_EPOCH;
+    let mut residency = health::Residency::default();
+    residency.record(start, "hold");
+    residency.record(start + Duration::from_secs(30), "slowDecrease");
+    residency.record(start + Duration::from_secs(40), "hold");
+    residency.record(start + Duration::from_secs(100), "hold");
+    assert_eq!(residency.transitions, 2);
+    assert_eq!(residency.total(), Duration::from_secs(100));
+    assert_eq!(residency.percent("hold"), Some(90.0));
+    assert_eq!(residency.percent("slowDecrease"), Some(10.0));
+    assert_eq!(residency.percent("critical"), Some(0.0));
+    assert_eq!(residency.level(), Some("hold"));
+    assert_eq!(health::Residency::default().percent("hold"), None);
+}
+
+#[test]
+fn long_gaps_are_not_credited() {
+    let start = SystemTime::UNIX_EPOCH;
+    let mut residency = health::Residency::default();
+    residency.record(start, "hold");
+    residency.record(start + Duration::from_secs(60), "critical");
+    // viewerator wasn't running for an hour
+    residency.record(start + Duration::from_secs(3660), "hold");
+    residency.record(start + Duration::from_secs(3700), "hold");
+    assert_eq!(residency.total(), Duration::from_secs(100));
+    assert_eq!(residency.percent("critical"), Some(0.0));
+    assert_eq!(residency.transitions, 2);
+}
+
+#[test]
+fn tracks_every_field() {
+    let wd = common::rig();
+    let w = &wd.workers[0];
+    let mut tracker = health::Tracker::default();
+    tracker.record_workers(SystemTime::UNIX_EPOCH, &wd.workers);
+    let fields = tracker.device(&w.hw_uid).unwrap();
+    assert!(fields.contains_key(&health::Field::InputPower));
+    assert!(fields.contains_key(&health::Field::Clock));
+    assert_eq!(fields.len(), health::fields(w).len());
+    assert!(health::LEVELS.iter().all(|level| !health::meaning(level).is_empty()));
+}
@@ -0,0 +1,89 @@
+use mylib::history::{History, Metric};
+use mylib::*;
+use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+#[test]
+fn capacity_drops_oldest() {
+    let mut h = History::new(Duration::from_secs(3600), 3);
+    for i in 0..5 {
+        h.record("dev", Metric::InputPower, at(i), i as f64);
+    }
+    let values: Vec<f64> = h
+        .series("dev", &Metric::InputPower)
+        .unwrap()
+        .iter()
+        .map(|s| s.value)
+        .collect();
+    assert_eq!(values, vec![2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn retention_expires_old_samples() {
+    let mut h = History::new(Duration::from_secs(60), 100);
+    h.record("dev", Metric::Multiplier, at(1000), 1.0);
+    h.record("dev", Metric::Multiplier, at(1010), 3.0);
+    h.record("gone", Metric::Multiplier, at(1000), 1.0);
+    h.record("dev", Metric::Multiplier, at(1050), 2.0);
+    h.expire(at(1075));
+    assert_eq!(h.series("dev", &Metric::Multiplier).unwrap().len(), 1);
+    // what aged out is averaged into the minute it was taken in, for the day long chart
+    let values: Vec<(SystemTime, f64)> = h
+        .since("dev", &Metric::Multiplier, at(0))
+        .iter()
+        .map(|s| (s.time, s.value))
+        .collect();
+    assert_eq!(values, vec![(at(960), 2.0), (at(1050), 2.0)]);
+    assert_eq!(h.since("dev", &Metric::Multiplier, at(1000)).len(), 1);
+
+    h.expire(at(1050 + history::DOWNSAMPLED_RETENTION_SECS));
+    assert_eq!(h.since("dev", &Metric::Multiplier, at(0)).len(), 0);
+    assert!(h.metrics("gone").is_empty());
+}
+
+#[test]
+fn record_parsed_workers() {
+    let mut wd = webdata::WebData::new();
+    let input = fs::read_to_string("tests/data/2bcu1cvp.json").unwrap();
+    wd.process_response(input);
+    let mut h = History::default();
+    h.record_workers(at(1000), &wd.workers);
+    let bcu = &wd.workers[0];
+    assert_eq!(bcu.hw_uid, "400200000117ab284cf08085");
+    let metrics = h.metrics(&bcu.hw_uid);
+    assert!(metrics.contains(&Metric::Phase0Vout));
+    assert!(metrics.contains(&Metric::SysmonTemperature(0)));
+    assert!(metrics.contains(&Metric::Accepted("eaglesong.eu.nicehash.com:3381".to_string())));
+    let power = h.series(&bcu.hw_uid, &Metric::InputPower).unwrap();
+    assert_eq!(power[0].value, f64::from(bcu.input_power));
+}
+
+#[test]
+fn cores_add_up_to_one_sample() {
+    let input = fs::read_to_string("tests/data/2bcu1cvp.json").unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&input).unwrap();
+    let cores = json["workers"]["bcu-09"]["devices"][0]["cores"].as_array_mut().unwrap();
+    let core = cores[0].clone();
+    cores.push(core);
+    let mut wd = webdata::WebData::new();
+    wd.process_response(json.to_string());
+    let w = &wd.workers[0];
+    assert_eq!(w.cores.cores.len(), 2);
+    let samples = history::samples(w);
+    let hashrate: Vec<f64> = samples
+        .iter()
+        .filter(|(metric, _)| *metric == Metric::Hashrate)
+        .map(|(_, value)| *value)
+        .collect();
+    let minute = w.cores.cores[0].stats.minute.calculated;
+    assert_eq!(hashrate, vec![f64::from(minute) * 2.0 / 60.0]);
+    let multiplier = samples
+        .iter()
+        .find(|(metric, _)| *metric == Metric::Multiplier)
+        .unwrap();
+    assert_eq!(multiplier.1, f64::from(w.cores.cores[0].clock.multiplier));
+}
@@ -0,0 +1,61 @@
+use mylib::keymap::*;
+use pancurses::Input;
+use std::collections::BTreeMap;
+
+#[test]
+fn presets() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.action(&Input::KeyDC), Some(Action::Quit));
+    assert_eq!(keymap.action(&Input::Character('q')), Some(Action::Quit));
+    assert_eq!(keymap.action(&Input::Character('h')), Some(Action::Health));
+    assert_eq!(keymap.action(&Input::Character('y')), None);
+    // page keys move between devices in every view, the log has its own
+    assert_eq!(keymap.action(&Input::KeyNPage), Some(Action::NextPage));
+    assert_eq!(keymap.action(&Input::KeySPrevious), Some(Action::LogPageOlder));
+    assert_eq!(keymap.action(&Input::Character('d')), Some(Action::LogPageNewer));
+    assert_eq!(parse_key("shift-pagedown"), Some(Input::KeySNext));
+
+    let vim = Keymap::new(&Bindings {
+        preset: "vim".to_string(),
+        bindings: BTreeMap::new(),
+    });
+    assert_eq!(vim.action(&Input::Character('h')), Some(Action::PreviousDevice));
+    assert_eq!(vim.action(&Input::Character('l')), Some(Action::NextDevice));
+    assert_eq!(vim.action(&Input::Character('H')), Some(Action::Health));
+    assert_eq!(vim.action(&Input::Character('\u{6}')), Some(Action::NextPage));
+
+    let top = Keymap::new(&Bindings {
+        preset: "top".to_string(),
+        bindings: BTreeMap::new(),
+    });
+    assert_eq!(top.action(&Input::Character('h')), Some(Action::Help));
+    for preset in PRESETS.iter() {
+        let keymap = Keymap::new(&Bindings {
+            preset: preset.to_string(),
+            bindings: BTreeMap::new(),
+        });
+        assert!(ACTIONS.iter().all(|a| !keymap.keys(*a).is_empty()), "{}", preset);
+    }
+}
+
+#[test]
+fn config_remaps() {
+    let mut bindings = BTreeMap::new();
+    bindings.insert("quit".to_string(), vec!["Ctrl-c".to_string(), "F10".to_string()]);
+    bindings.insert("pause".to_string(), vec!["h".to_string(), "NoSuchKey".to_string()]);
+    bindings.insert("fly".to_string(), vec!["f".to_string()]);
+    let keymap = Keymap::new(&Bindings {
+        preset: "nonsense".to_string(),
+        bindings,
+    });
+    assert_eq!(keymap.action(&Input::KeyDC), None);
+    assert_eq!(keymap.action(&Input::Character('\u{3}')), Some(Action::Quit));
+    assert_eq!(keymap.action(&Input::KeyF10), Some(Action::Quit));
+    assert_eq!(keymap.action(&Input::Character('h')), Some(Action::Pause));
+    assert_eq!(keymap.action(&Input::Character(' ')), None);
+    assert_eq!(keymap.describe(Action::Quit), "Ctrl-c/F10");
+
+    assert_eq!(parse_key("pageup"), Some(Input::KeyPPage));
+    assert_eq!(key_name(&Input::Character('\t')), "Tab");
+    assert_eq!(parse_key("F13"), None);
+}
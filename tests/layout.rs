@@ -0,0 +1,174 @@
+use mylib::layout::*;
+
+fn overlaps(a: &Rect, b: &Rect) -> bool {
+    a.x < b.right() && b.x < a.right() && a.y < b.bottom() && b.y < a.bottom()
+}
+
+#[test]
+fn wide_keeps_classic_positions() {
+    let content = Content {
+        bcu: true,
+        sparkline_width: 0,
+        sysmons: 2,
+        hidden: vec![],
+        zoom: None,
+    };
+    let layout = Layout::new(126, 26, &content);
+    assert!(!layout.compact);
+    assert_eq!(layout.get(Panel::Stats), Some(Rect::new(14, 0, 9, 126)));
+    assert_eq!(layout.get(Panel::Phases), Some(Rect::new(4, 26, 9, 22)));
+    assert_eq!(layout.get(Panel::Clock), Some(Rect::new(4, 50, 4, 38)));
+    assert_eq!(layout.get(Panel::Log).unwrap().y, 23);
+    assert_eq!(layout.get(Panel::Events).unwrap().x, 78);
+    assert!(layout.get(Panel::Regulator).is_some());
+    // what a click at a spot lands on
+    let log = layout.get(Panel::Log).unwrap();
+    assert!(log.contains(23, 0));
+    assert!(log.contains(25, 76));
+    assert!(!log.contains(22, 0));
+    assert!(!log.contains(23, 77));
+
+    // a taller terminal gives the log the extra rows
+    let layout = Layout::new(160, 50, &content);
+    assert_eq!(layout.get(Panel::Log).unwrap().height, 27);
+}
+
+fn assert_fits(layout: &Layout, width: i32, height: i32) {
+    let rects: Vec<Rect> = PANELS.iter().filter_map(|p| layout.get(*p)).collect();
+    for (i, a) in rects.iter().enumerate() {
+        assert!(a.x >= 0 && a.right() <= width, "{:?} in {}x{}", a, width, height);
+        // the summary sits in the header rows of the wide layout
+        assert!(a.y >= 2 && a.bottom() <= height, "{:?} in {}x{}", a, width, height);
+        for b in &rects[i + 1..] {
+            assert!(!overlaps(a, b), "{:?} overlaps {:?} in {}x{}", a, b, width, height);
+        }
+    }
+}
+
+#[test]
+fn fits_without_overlap() {
+    // BCU1525s have 3 sysmons and CVP13s 4
+    for (bcu, sysmons) in [(true, 3), (false, 4), (true, 4), (true, 2)].iter() {
+        for sparkline_width in [0, 9, 12].iter() {
+            let content = Content {
+                bcu: *bcu,
+                sparkline_width: *sparkline_width,
+                sysmons: *sysmons,
+                hidden: vec![],
+                zoom: None,
+            };
+            for (width, height) in [(80, 24), (100, 30), (60, 20), (126, 20)].iter() {
+                let layout = Layout::new(*width, *height, &content);
+                assert!(layout.compact);
+                assert!(layout.get(Panel::Summary).into_iter().all(|r| r.y >= BODY_TOP));
+                assert_fits(&layout, *width, *height);
+            }
+            for (width, height) in [(126, 25), (126, 26), (160, 50)].iter() {
+                assert_fits(&Layout::new(*width, *height, &content), *width, *height);
+            }
+        }
+    }
+    let content = Content {
+        bcu: true,
+        sparkline_width: 0,
+        sysmons: 3,
+        hidden: vec![],
+        zoom: None,
+    };
+    // room for the regulator right of a BCU's sysmons, but not a CVP's
+    let layout = Layout::new(126, 26, &content);
+    assert!(!layout.compact);
+    assert_eq!(layout.get(Panel::Regulator).unwrap().x, 98);
+    let content = Content {
+        bcu: false,
+        sysmons: 4,
+        ..content
+    };
+    let layout = Layout::new(126, 26, &content);
+    assert!(!layout.compact);
+    assert!(layout.get(Panel::Regulator).is_none());
+    assert!(layout.get(Panel::Stats).is_some());
+    assert!(layout.get(Panel::Phases).is_none());
+
+    // too small for anything but shouldn't panic
+    let layout = Layout::new(5, 3, &content);
+    assert!(PANELS.iter().all(|p| layout.get(*p).is_none()));
+}
+
+#[test]
+fn hide_and_zoom_panels() {
+    let mut preferences = Preferences::default();
+    assert!(!preferences.toggle(Panel::Stats));
+    assert!(!preferences.toggle(Panel::Events));
+    let content = Content {
+        bcu: true,
+        sparkline_width: 0,
+        sysmons: 2,
+        hidden: preferences.hidden.clone(),
+        zoom: None,
+    };
+    // the log moves up into the room the stats had and takes the event pane's
+    let layout = Layout::new(126, 26, &content);
+    assert!(layout.get(Panel::Stats).is_none() && layout.get(Panel::Events).is_none());
+    assert_eq!(layout.get(Panel::Log), Some(Rect::new(14, 0, 12, 126)));
+    let layout = Layout::new(80, 24, &content);
+    assert!(layout.get(Panel::Stats).is_none());
+    assert!(preferences.toggle(Panel::Stats));
+    assert_eq!(preferences.hidden, vec![Panel::Events]);
+
+    // zooming steps through the panels and back, past phases on a CVP
+    assert_eq!(preferences.next_zoom(false), Some(Panel::Log));
+    assert_eq!(preferences.next_zoom(false), Some(Panel::Stats));
+    assert_eq!(preferences.next_zoom(false), Some(Panel::Sysmons));
+    assert_eq!(preferences.next_zoom(false), Some(Panel::Events));
+    assert_eq!(preferences.next_zoom(false), None);
+    let content = Content {
+        zoom: Some(Panel::Events),
+        ..content
+    };
+    let layout = Layout::new(100, 30, &content);
+    assert_eq!(layout.get(Panel::Events), Some(Rect::new(BODY_TOP, 0, 26, 100)));
+    assert_eq!(layout.zoomed, Some(Panel::Events));
+    // a CVP has no phases to zoom, so it gets the usual layout
+    let cvp = Content {
+        bcu: false,
+        zoom: Some(Panel::Phases),
+        ..content.clone()
+    };
+    assert_eq!(Layout::new(100, 30, &cvp).zoomed, None);
+    assert!(PANELS.iter().filter(|p| layout.get(**p).is_some()).count() == 1);
+
+    let json = serde_json::to_string(&Preferences {
+        hidden: vec![Panel::Energy],
+        zoom: Some(Panel::Log),
+    })
+    .unwrap();
+    assert_eq!(json, r#"{"hidden":["energy"],"zoom":"log"}"#);
+    assert_eq!(Panel::parse("SYS"), Some(Panel::Sysmons));
+    assert_eq!(Panel::parse(""), None);
+}
+
+#[test]
+fn help_scrolls_to_every_line() {
+    // as many lines as the help has in the chart view, with its own keys
+    let lines = 52;
+    for (width, height) in [(80, 24), (126, 26), (200, 60)].iter() {
+        let r = help_box(*width, *height).unwrap();
+        assert!(r.x >= 0 && r.right() <= *width && r.y >= 0 && r.bottom() <= *height);
+        let columns = Columns::new(r, HELP_COLUMN_WIDTH);
+        let max_scroll = columns.max_scroll(lines);
+        for line in 0..lines {
+            let (row, column) = (0..=max_scroll)
+                .find_map(|scroll| columns.place(line, scroll))
+                .unwrap_or_else(|| panic!("line {} never shows in {}x{}", line, width, height));
+            assert!(row >= 0 && row < r.height - 2);
+            assert!(column >= 0 && column + columns.width <= r.width - 4);
+        }
+    }
+    let columns = Columns::new(help_box(80, 24).unwrap(), HELP_COLUMN_WIDTH);
+    assert_eq!((columns.count, columns.rows), (1, 20));
+    assert_eq!(columns.max_scroll(lines), 32);
+    let columns = Columns::new(help_box(126, 26).unwrap(), HELP_COLUMN_WIDTH);
+    assert_eq!(columns.count, 2);
+    assert!(help_box(20, 6).is_none());
+}
@@ -0,0 +1,83 @@
+use mylib::log_display::*;
+
+fn lines(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| format!("{} INF line {}", i, if i % 10 == 0 { "Found nonce" } else { "hashing" }))
+        .collect()
+}
+
+#[test]
+fn scrolls_without_underflow() {
+    let mut view = LogView::new();
+    // fewer lines than fit used to underflow
+    view.set_lines(lines(3));
+    assert_eq!(view.window(10), 0..3);
+
+    view.set_lines(lines(100));
+    assert_eq!(view.window(10), 90..100);
+    view.page(true);
+    assert!(!view.follow);
+    assert_eq!(view.window(10), 81..91);
+    // paused, so new lines wait until it follows again
+    view.set_lines(lines(120));
+    assert_eq!(view.lines().len(), 100);
+    view.scroll_by(1000);
+    assert_eq!(view.window(10), 0..10);
+    view.toggle_follow();
+    view.set_lines(lines(120));
+    assert_eq!(view.window(10), 110..120);
+}
+
+#[test]
+fn searches_and_highlights() {
+    let mut view = LogView::new();
+    view.set_lines(lines(100));
+    view.window(10);
+    assert!(view.search("found NONCE"));
+    assert_eq!(view.current, Some(90));
+    assert_eq!(view.match_count(), 10);
+    assert!(view.window(10).contains(&90));
+    assert!(view.next(true));
+    assert_eq!(view.current, Some(80));
+    assert!(view.window(10).contains(&80));
+    assert!(view.next(false));
+    assert_eq!(view.current, Some(90));
+    assert!(!view.next(false));
+    assert!(!view.search("no such thing"));
+    assert_eq!(view.current, None);
+
+    assert_eq!(highlights("abcABCabc", "bc"), vec![1..3, 4..6, 7..9]);
+    assert!(highlights("abc", "").is_empty());
+}
+
+#[test]
+fn filter_modes() {
+    let info = "12:00:00 INF BCU-0: Found nonce";
+    let other = "12:00:00 INF BCU-1: Found nonce";
+    let warning = "12:00:01 WRN Pool disconnected";
+    let error = "12:00:02 ERR BCU-1: Overtemp";
+    let fee = "12:00:03 INF Fee mining started";
+
+    let device = Filter::default();
+    assert!(device.matches(info, "BCU-0") && device.matches(fee, "BCU-0"));
+    assert!(!device.matches(other, "BCU-0") && !device.matches(warning, "BCU-0"));
+    assert!(Filter::All.matches(warning, "BCU-0"));
+    assert!(Filter::Warnings.matches(warning, "") && Filter::Warnings.matches(error, ""));
+    assert!(!Filter::Warnings.matches(info, ""));
+    assert!(Filter::Errors.matches(error, "") && !Filter::Errors.matches(warning, ""));
+
+    let regex = Filter::regex("(?i)pool|overtemp").unwrap();
+    assert!(regex.matches(warning, "") && regex.matches(error, "") && !regex.matches(info, ""));
+    assert!(Filter::regex("(unclosed").is_err());
+
+    // real minerator lines, where only the level field counts
+    let garbage = "[2020-02-14T21:36:36.589Z] WRN 0000:17:00.0:10000: Got garbage share: nonce 00000000814a94f4";
+    let named = "[2020-02-14T21:36:32.401Z] INF ERR-rig-WRN: Got job from us-ckb.2miners.com:6565";
+    assert_eq!(level(garbage), Some("WRN"));
+    assert!(Filter::Warnings.matches(garbage, "") && !Filter::Errors.matches(garbage, ""));
+    assert!(!Filter::Warnings.matches(named, "") && !Filter::Errors.matches(named, ""));
+
+    assert!(matches!(device.next(), Filter::All));
+    assert!(matches!(Filter::Errors.next(), Filter::Device));
+    assert!(matches!(regex.next(), Filter::Device));
+}
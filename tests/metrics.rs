@@ -0,0 +1,89 @@
+mod common;
+
+use mylib::*;
+use std::fs;
+
+#[test]
+fn efficiency_needs_power() {
+    assert_eq!(metrics::efficiency(100.0, 50.0), Some(2.0));
+    assert_eq!(metrics::efficiency(100.0, 0.0), None);
+}
+
+#[test]
+fn device_and_fleet_efficiency() {
+    let wd = common::rig();
+    let w = &wd.workers[0];
+    let device = metrics::device_efficiency(w);
+    let minute = metrics::minute_rate(w.cores.cores[0].stats.minute.accepted) / w.input_power;
+    assert_eq!(device.minute, Some(minute));
+
+    let fleet = metrics::fleet_efficiency(&wd.workers);
+    let power: f32 = wd.workers.iter().map(|w| w.input_power).sum();
+    assert_eq!(metrics::fleet_power(&wd.workers), power);
+    assert!(fleet.total.unwrap() > 0.0);
+}
+
+#[test]
+fn effective_hashrate_from_shares() {
+    let wd = common::rig();
+    let ws = &wd.workers[0].worksource;
+    let shares = metrics::accepted_shares(ws, &ws.stats.minute).unwrap();
+    assert_eq!(shares, 35.0);
+    let effective = metrics::effective_hashrate(ws);
+    let accepted = metrics::minute_rate(ws.stats.minute.accepted);
+    assert!((effective.minute.unwrap() - accepted).abs() / accepted < 0.001);
+
+    // difficulty in units of hashesPerDiff1, 128G hashes a share
+    let mut wd = webdata::WebData::new();
+    wd.process_response(fs::read_to_string("tests/data/4bcu.json").unwrap());
+    let ws = &wd.workers[0].worksource;
+    assert_eq!(ws.hashes_per_share().round(), 128e9);
+    assert_eq!(metrics::accepted_shares(ws, &ws.stats.minute), Some(18.0));
+    let effective = metrics::effective_hashrate(ws);
+    assert!((effective.minute.unwrap() - 18.0 * 128_000.0 / 60.0).abs() < 0.1);
+    let calculated = metrics::minute_rate(ws.stats.minute.calculated);
+    let gap = metrics::hashrate_gap(effective.minute, calculated).unwrap();
+    assert!((gap + 14.03).abs() < 0.01);
+
+    assert_eq!(metrics::hashrate_gap(Some(90.0), 100.0), Some(-10.0));
+    assert_eq!(metrics::hashrate_gap(Some(90.0), 0.0), None);
+    assert_eq!(metrics::hashrate_gap(None, 100.0), None);
+}
+
+#[test]
+fn quality_percentages() {
+    let wd = common::rig();
+    let clock = &wd.workers[0].cores.cores[0].clock;
+    let bad = metrics::bad_nonce_percent(clock).unwrap();
+    assert!((bad - clock.badNonces / clock.totalNonces * 100.0).abs() < 1e-4);
+
+    let stat = webdata::StatDetail {
+        found: 200.0,
+        valid: 196.0,
+        submitted: 100.0,
+        accepted: 97.0,
+        ..Default::default()
+    };
+    assert_eq!(metrics::valid_percent(&stat), Some(98.0));
+    assert_eq!(metrics::reject_percent(&stat), Some(3.0));
+    assert_eq!(metrics::reject_percent(&webdata::StatDetail::default()), None);
+
+    let config = config::Config::default();
+    assert_eq!(config.reject_percent.health(3.0), "critical");
+    assert_eq!(config.reject_percent.health(0.5), "");
+    assert_eq!(config.valid_percent.health(97.0), "hold");
+    assert_eq!(config.valid_percent.health(99.0), "");
+}
+
+#[test]
+fn rate_needs_elapsed_time() {
+    let stat = webdata::StatDetail {
+        startTime: 2e9,
+        endTime: 2e9,
+        accepted: 100.0,
+        ..Default::default()
+    };
+    assert_eq!(metrics::total_rate(&stat, stat.accepted), 0.0);
+    let backwards = webdata::StatDetail { endTime: 1e9, ..stat };
+    assert_eq!(metrics::total_rate(&backwards, backwards.accepted), 0.0);
+}
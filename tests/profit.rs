@@ -0,0 +1,37 @@
+use mylib::*;
+
+fn profit() -> profit::Profit {
+    profit::Profit {
+        coin_price: 0.01,
+        network_difficulty: 1e15,
+        block_reward: 1000.0,
+        electricity_rate: 0.10,
+        tariffs: vec![profit::Tariff {
+            from_hour: 22,
+            to_hour: 6,
+            rate: 0.05,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn time_of_use_rates() {
+    let profit = profit();
+    assert_eq!(profit.rate_at(23), 0.05);
+    assert_eq!(profit.rate_at(3), 0.05);
+    assert_eq!(profit.rate_at(6), 0.10);
+    // 1kW for 8 cheap and 16 normal hours
+    assert!((profit.cost_per_day(1000.0) - (8.0 * 0.05 + 16.0 * 0.10)).abs() < 1e-9);
+}
+
+#[test]
+fn daily_estimate() {
+    let profit = profit();
+    // 10 GH/s is 8.64e14 hashes a day, 0.864 blocks
+    let estimate = profit.estimate(10_000.0, 100.0);
+    assert!((estimate.revenue - 0.864 * 1000.0 * 0.01).abs() < 1e-6);
+    assert!((estimate.cost - 0.2).abs() < 1e-9);
+    assert!((estimate.profit - (estimate.revenue - estimate.cost)).abs() < 1e-9);
+    assert_eq!(profit::Profit::default().revenue_per_day(10_000.0), 0.0);
+}
@@ -0,0 +1,27 @@
+mod common;
+
+use mylib::*;
+
+#[test]
+fn droop_and_imbalance() {
+    assert!((regulator::droop(0.6, 0.61).unwrap() - 1.639).abs() < 0.01);
+    assert!(regulator::droop(0.62, 0.61).unwrap() < 0.0);
+    assert_eq!(regulator::droop(0.6, 0.0), None);
+    assert!((regulator::imbalance(0.99, 1.01).unwrap() - 2.0).abs() < 1e-4);
+}
+
+#[test]
+fn flags_phase_asymmetry() {
+    let mut wd = common::rig();
+    let tolerance = config::Regulator::default();
+    let w = &mut wd.workers[0];
+    let readings = regulator::readings(w, &tolerance);
+    assert_eq!(readings.len(), 4);
+    assert!(regulator::worst(&readings).is_none());
+
+    w.phase1_temperature = w.phase0_temperature + 12.0;
+    let readings = regulator::readings(w, &tolerance);
+    let worst = regulator::worst(&readings).unwrap();
+    assert_eq!(worst.label, "temp imbalance");
+    assert_eq!(worst.health, "critical");
+}
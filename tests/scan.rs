@@ -0,0 +1,34 @@
+use mylib::*;
+use std::fs;
+
+#[test]
+fn expand_subnet() {
+    let hosts = scan::expand_targets(&["10.0.0.0/30"]).unwrap();
+    assert_eq!(hosts, vec!["http://10.0.0.1", "http://10.0.0.2"]);
+    assert_eq!(scan::expand_targets(&["192.168.1.7/24"]).unwrap().len(), 254);
+}
+
+#[test]
+fn expand_host_list() {
+    let hosts = scan::expand_targets(&["10.0.0.5,rig2", "http://rig3:8080/"]).unwrap();
+    assert_eq!(hosts, vec!["http://10.0.0.5", "http://rig2", "http://rig3:8080"]);
+    // a host inside a subnet given too is only probed once
+    let hosts = scan::expand_targets(&["10.0.0.2", "10.0.0.0/30", "10.0.0.2"]).unwrap();
+    assert_eq!(hosts, vec!["http://10.0.0.2", "http://10.0.0.1"]);
+}
+
+#[test]
+fn expand_rejects_bad_targets() {
+    assert!(scan::expand_targets(&["10.0.0.0/33"]).is_err());
+    assert!(scan::expand_targets(&["10.0.0/24"]).is_err());
+    assert!(scan::expand_targets(&["10.0.0.0/8"]).is_err());
+}
+
+#[test]
+fn summarize_status() {
+    let input = fs::read_to_string("tests/data/2bcu1cvp.json").unwrap();
+    let (minerator, devices) = webdata::WebData::status_summary(&input).unwrap();
+    assert_eq!(minerator, "00.00.00");
+    assert_eq!(devices, 3);
+    assert!(webdata::WebData::status_summary("<html></html>").is_none());
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use mylib::history::Metric;
+use mylib::*;
+
+#[test]
+fn running_min_max_avg() {
+    let mut running = session::Running::new(2.0);
+    running.record(6.0);
+    running.record(1.0);
+    assert_eq!(running.min, 1.0);
+    assert_eq!(running.max, 6.0);
+    assert_eq!(running.last, 1.0);
+    assert_eq!(running.avg(), 3.0);
+    assert_eq!(running.count, 3);
+}
+
+#[test]
+fn session_covers_sysmons_and_resets() {
+    let wd = common::rig();
+    let mut session = session::Session::default();
+    session.record_workers(&wd.workers);
+    session.record_workers(&wd.workers);
+    let w = &wd.workers[0];
+    let vccbram = session.get(&w.hw_uid, &Metric::SysmonVccbram(0)).unwrap();
+    assert_eq!(vccbram.count, 2);
+    assert_eq!(vccbram.max, f64::from(w.sysmons.sysmon[0].vccbram));
+    session.reset();
+    assert!(session.get(&w.hw_uid, &Metric::InputPower).is_none());
+}
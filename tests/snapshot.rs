@@ -0,0 +1,27 @@
+use mylib::*;
+use std::env;
+use std::fs;
+
+#[test]
+fn snapshot_path_is_per_host() {
+    let a = snapshot::path("/tmp", "http://10.0.0.5");
+    let b = snapshot::path("/tmp", "http://rig2:8080");
+    assert_eq!(a.to_str().unwrap(), "/tmp/snapshot-10.0.0.5.json");
+    assert_eq!(b.to_str().unwrap(), "/tmp/snapshot-rig2_8080.json");
+}
+
+#[test]
+fn load_snapshot_is_stale() {
+    let dir = env::temp_dir().join("viewerator-snapshot-test");
+    fs::create_dir_all(&dir).unwrap();
+    let dir = dir.to_str().unwrap();
+    let input = fs::read_to_string("tests/data/2bcu1cvp.json").unwrap();
+    snapshot::Snapshot::save(dir, "http://rig1", &input).unwrap();
+
+    let mut wd = webdata::WebData::new();
+    assert!(wd.load_snapshot(dir, "http://rig1"));
+    assert_eq!(wd.workers.len(), 3);
+    assert!(wd.stale);
+    assert!(wd.age().is_some());
+    assert!(!wd.load_snapshot(dir, "http://rig9"));
+}
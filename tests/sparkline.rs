@@ -0,0 +1,24 @@
+use mylib::history::Sample;
+use mylib::sparkline;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn render_scales_between_min_and_max() {
+    let line = sparkline::render(&[Some(1.0), Some(2.0), None, Some(8.0)]);
+    assert_eq!(line, "▁▂ █");
+    assert_eq!(sparkline::render(&[Some(5.0), Some(5.0)]), "▁▁");
+}
+
+#[test]
+fn bucket_averages_by_time() {
+    let start = UNIX_EPOCH + Duration::from_secs(1000);
+    let samples: Vec<Sample> = [(1000, 1.0), (1001, 3.0), (1030, 7.0), (1080, 9.0)]
+        .iter()
+        .map(|(t, v)| Sample {
+            time: UNIX_EPOCH + Duration::from_secs(*t),
+            value: *v,
+        })
+        .collect();
+    let buckets = sparkline::bucket(&samples, start, start + Duration::from_secs(80), 4);
+    assert_eq!(buckets, vec![Some(2.0), Some(7.0), None, Some(9.0)]);
+}
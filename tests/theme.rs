@@ -0,0 +1,51 @@
+use mylib::*;
+use std::collections::BTreeMap;
+
+#[test]
+fn resolves_themes() {
+    let mut settings = theme::Settings::default();
+    // worst first, the default keeps the original white on red for critical
+    let colors = theme::resolve(&settings, 8);
+    assert_eq!(colors.len(), health::LEVELS.len());
+    assert_eq!(colors[0], (7, 1));
+    assert_eq!(colors[4], (0, 2));
+
+    settings.name = "colorblind".to_string();
+    let colorblind = theme::resolve(&settings, 8);
+    assert_ne!(colorblind[1].1, 5);
+    assert!(theme::BUILT_IN.iter().all(|name| theme::built_in(name).is_some()));
+
+    // a custom theme with a 256 color index the terminal may not have
+    let mut palette = BTreeMap::new();
+    palette.insert("critical".to_string(), theme::Colors::new("white", "196"));
+    palette.insert("hold".to_string(), theme::Colors::new("black", "mauve"));
+    settings.themes.insert("mine".to_string(), palette);
+    settings.name = "mine".to_string();
+    assert_eq!(theme::resolve(&settings, 256)[0], (7, 196));
+    assert_eq!(theme::resolve(&settings, 8)[0], (7, 1));
+    assert_eq!(theme::resolve(&settings, 256)[2], (0, 3));
+    assert_eq!(theme::resolve(&settings, 256)[4], (0, 2));
+
+    settings.name = "nonesuch".to_string();
+    assert_eq!(
+        theme::resolve(&settings, 8),
+        theme::resolve(&theme::Settings::default(), 8)
+    );
+}
+
+#[test]
+fn monochrome_and_gradient() {
+    let settings = theme::Settings::default();
+    assert!(!theme::monochrome(&settings, None));
+    assert!(!theme::monochrome(&settings, Some("")));
+    assert!(theme::monochrome(&settings, Some("1")));
+    assert_eq!(theme::marker("critical"), "!!");
+    assert_eq!(theme::marker("rampUp"), "");
+
+    assert_eq!(theme::gradient_step(20.0, 40.0, 90.0, 16), 0);
+    assert_eq!(theme::gradient_step(65.0, 40.0, 90.0, 16), 8);
+    assert_eq!(theme::gradient_step(120.0, 40.0, 90.0, 16), 15);
+    assert_eq!(theme::gradient_step(f32::NAN, 40.0, 90.0, 16), 0);
+    assert_eq!(theme::gradient_rgb(0.0), (0, 0, 1000));
+    assert_eq!(theme::gradient_rgb(1.0), (1000, 0, 0));
+}
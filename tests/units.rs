@@ -0,0 +1,34 @@
+use mylib::*;
+
+#[test]
+fn parse_and_pick_units() {
+    assert_eq!(units::parse("GH"), Some(3));
+    assert_eq!(units::parse("th/s"), Some(4));
+    assert_eq!(units::parse("H"), Some(0));
+    assert_eq!(units::parse("XH"), None);
+    assert_eq!(units::auto(0.0), 0);
+    assert_eq!(units::auto(f64::NAN), 0);
+    assert_eq!(units::auto(2.5e9), 3);
+    assert_eq!(units::auto(1e30), 6);
+    assert_eq!(units::label(3, "/s"), "GH/s");
+    // stats count in MH
+    assert_eq!(units::scale_stat(2500.0, 3), 2.5);
+}
+
+#[test]
+fn compact_numbers_fit() {
+    assert_eq!(units::compact(12.34), "   12.3");
+    assert_eq!(units::compact(-12.34), "  -12.3");
+    assert_eq!(units::compact(2.5e12), "   2.5T");
+    assert_eq!(units::compact(f32::INFINITY), "      -");
+    assert!(units::compact(1e30).len() <= 7);
+
+    assert_eq!(units::short(0.8512), "0.85");
+    assert_eq!(units::short(41.26), "41.3");
+    assert_eq!(units::short(123.4), "123");
+    assert_eq!(units::short(123456.0), "123K");
+    assert_eq!(units::short(2.5e9), "2.5G");
+    assert!([0.85, 9.99, 99.97, 999.7, 1.0e6, 12345.0, -5.5]
+        .iter()
+        .all(|v| units::short(*v).len() <= 5));
+}